@@ -0,0 +1,104 @@
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::commands::ApplicationArguments;
+use crate::utils::agent;
+
+
+pub fn agent_start(_args: &ApplicationArguments, start_options: &AgentStartOptions) {
+    if agent::is_running() {
+        eprintln!("Error: password agent is already running");
+        std::process::exit(1);
+    }
+    let executable = match std::env::current_exe() {
+        Ok(executable) => executable,
+        Err(e) => {
+            eprintln!("Error: can't start password agent: {}", e);
+            std::process::exit(1);
+        }
+    };
+    //own process group, so that ctrl+c in terminal doesn't stop agent
+    let spawned = Command::new(executable)
+        .arg("agent")
+        .arg("run")
+        .arg("--timeout")
+        .arg(start_options.timeout.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn();
+    if let Err(e) = spawned {
+        eprintln!("Error: can't start password agent: {}", e);
+        std::process::exit(1);
+    }
+    for _ in 0..50 {
+        if agent::is_running() {
+            println!("Password agent started, passwords will be remembered for {} seconds", start_options.timeout);
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    eprintln!("Error: password agent didn't start");
+    std::process::exit(1);
+}
+
+pub fn agent_stop(_args: &ApplicationArguments) {
+    if !agent::stop() {
+        eprintln!("Error: password agent is not running");
+        std::process::exit(1);
+    }
+}
+
+pub fn agent_status(_args: &ApplicationArguments) {
+    if agent::is_running() {
+        println!("running");
+    } else {
+        println!("not running");
+    }
+}
+
+pub fn agent_run(_args: &ApplicationArguments, run_options: &AgentStartOptions) {
+    if let Err(e) = agent::run(run_options.timeout) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+pub fn agent(args: &ApplicationArguments, agent_command: &AgentCommand) {
+    match &agent_command.command {
+        AgentSubCommand::Start(start_options) => agent_start(&args, &start_options),
+        AgentSubCommand::Stop => agent_stop(&args),
+        AgentSubCommand::Status => agent_status(&args),
+        AgentSubCommand::Run(run_options) => agent_run(&args, &run_options),
+    };
+}
+
+#[derive(Clone, StructOpt)]
+pub struct AgentCommand {
+    #[structopt(subcommand)]
+    pub command: AgentSubCommand,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub enum AgentSubCommand {
+    #[structopt(name = "start", about="start agent remembering passwords typed in terminal (and vault passphrase)")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Start(AgentStartOptions),
+    #[structopt(name = "stop", about="stop agent, forgetting passwords")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Stop,
+    #[structopt(name = "status", about="check if agent is running")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Status,
+    #[structopt(name = "run", about="run agent in foreground (used by start)")]
+    #[structopt(setting = structopt::clap::AppSettings::Hidden)]
+    Run(AgentStartOptions),
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct AgentStartOptions {
+    #[structopt(long = "timeout", help = "seconds after which agent exits", default_value = "900")]
+    pub timeout: u64,
+}
@@ -2,11 +2,17 @@ use structopt;
 use structopt::StructOpt;
 use structopt::clap::arg_enum;
 
+use crate::config::Settings;
+
+#[cfg(unix)]
+pub mod agent;
 pub mod common;
 pub mod export;
 pub mod schema;
 pub mod shell;
 pub mod sources;
+#[cfg(feature = "use_vault")]
+pub mod vault;
 
 
 arg_enum! {
@@ -25,12 +31,36 @@ arg_enum! {
 pub struct ApplicationArguments {
     #[structopt(short = "v", long = "verbose", help = "Be verbose")]
     pub verbose: bool,
-    #[structopt(short = "c", long = "color", help = "use color", default_value="auto", possible_values = &UseColor::variants(), case_insensitive = true)]
-    pub color: UseColor,
+    #[structopt(long = "no-verbose", help = "Don't be verbose, even if config file says so", conflicts_with = "verbose")]
+    pub no_verbose: bool,
+    #[structopt(short = "c", long = "color", help = "use color [default: auto]", possible_values = &UseColor::variants(), case_insensitive = true)]
+    pub color: Option<UseColor>,
     #[structopt(subcommand)]
     pub command: Command,
 }
 
+impl ApplicationArguments {
+
+    ///fill options not given on command line from settings, using overrides for source command is run with
+    pub fn update_from_settings(&mut self, settings: &Settings) {
+        let settings = match &self.command {
+            Command::Export(export_command) => settings.for_source(&export_command.source.1),
+            Command::Shell(shell_command) => settings.for_source(&shell_command.source.1),
+            Command::Schema(schema_command) => settings.for_source(&schema_command.source.1),
+            _ => settings.clone(),
+        };
+        if self.color.is_none() {
+            self.color = settings.color.as_ref().and_then(|color| color.parse().ok());
+        }
+        self.verbose = !self.no_verbose && (self.verbose || settings.verbose.unwrap_or(false));
+        match &mut self.command {
+            Command::Export(export_command) => export_command.update_from_settings(&settings),
+            Command::Shell(shell_command) => shell_command.update_from_settings(&settings),
+            _ => {},
+        }
+    }
+}
+
 
 #[derive(StructOpt)]
 pub enum Command {
@@ -46,4 +76,43 @@ pub enum Command {
     #[structopt(name = "sources", about="manage data sources")]
     #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
     Sources(sources::SourcesCommand),
+    #[cfg(feature = "use_vault")]
+    #[structopt(name = "vault", about="manage passwords stored in encrypted vault")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Vault(vault::VaultCommand),
+    #[cfg(unix)]
+    #[structopt(name = "agent", about="remember passwords typed in terminal for a while")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Agent(agent::AgentCommand),
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use structopt::StructOpt;
+
+    use super::{ApplicationArguments, Command};
+    use crate::config::Settings;
+
+    #[cfg(all(feature = "use_generate", feature = "use_text", feature = "use_csv"))]
+    #[test]
+    fn test_command_line_overrides_settings() {
+        let settings: Settings = toml::from_str("verbose = true\ntruncate = 20\n").unwrap();
+        let parse = |args: &[&str]| {
+            let mut args = ApplicationArguments::from_iter_safe(args).unwrap();
+            args.update_from_settings(&settings);
+            let truncate = match &mut args.command {
+                Command::Export(export_command) => export_command.source.0.destination_mut().truncate_mut().truncate,
+                _ => unreachable!(),
+            };
+            (args.verbose, truncate)
+        };
+        assert_eq!(parse(&["dbfish", "export", "generate", "id:serial", "text", "-"]), (true, Some(20)));
+        assert_eq!(parse(&["dbfish", "export", "generate", "id:serial", "text", "-t", "5", "-"]), (true, Some(5)));
+        assert_eq!(parse(&["dbfish", "--no-verbose", "export", "generate", "id:serial", "text", "--no-truncate", "-"]), (false, None));
+        //files and databases are not truncated by settings
+        assert_eq!(parse(&["dbfish", "export", "generate", "id:serial", "csv", "-"]), (true, None));
+    }
+
 }
@@ -11,6 +11,8 @@ use crate::commands::{ApplicationArguments};
 use crate::commands::common::{SourceConfigCommandWrapper, SourceConfigCommand};
 use crate::utils::report_query_error;
 
+#[cfg(feature = "use_duckdb")]
+use crate::sources::duckdb::establish_duckdb_connection;
 #[cfg(feature = "use_mysql")]
 use crate::sources::mysql::{establish_mysql_connection};
 #[cfg(feature = "use_postgres")]
@@ -132,7 +134,7 @@ pub fn schema (_args: &ApplicationArguments, schema_command: &SchemaCommand) {
     match &schema_command.source.0 {
         #[cfg(feature = "use_mysql")]
         SourceConfigCommand::Mysql(mysql_config_options) => {
-            let mut conn = establish_mysql_connection(mysql_config_options);
+            let (mut conn, _tunnel, _temp_files) = establish_mysql_connection(mysql_config_options);
             let mut where_parts = vec![];
             let mut params = vec![];
             if let Some(dbname) = &mysql_config_options.database {
@@ -340,9 +342,97 @@ pub fn schema (_args: &ApplicationArguments, schema_command: &SchemaCommand) {
             }
             dbitems.print();
         },
+        #[cfg(feature = "use_duckdb")]
+        SourceConfigCommand::Duckdb(duckdb_config_options) => {
+            let conn = establish_duckdb_connection(duckdb_config_options);
+            let query = "
+                select
+                    table_schema,
+                    table_name,
+                    column_name,
+                    data_type,
+                    is_nullable
+                from
+                    information_schema.columns
+                order by table_schema, table_name, ordinal_position
+                ";
+            let mut stmt = match conn.prepare(query) {
+                Ok(v) => v,
+                Err(e) => {
+                    report_query_error(&query, &format!("{}", e));
+                    std::process::exit(1);
+                }
+            };
+            let mut results = stmt.query([]).unwrap();
+            let mut dbitems = DBItems::new();
+            let root_node = dbitems.0.insert(
+                Node::new(
+                    DBItem{name: "".to_string(), description: None}
+                ),
+                InsertBehavior::AsRoot
+            ).unwrap();
+            let mut current_schema = None;
+            let mut current_table = None;
+
+            while let Some(row) = results.next().unwrap() {
+                let schema_name: String = row.get(0).unwrap();
+                let table_name: String = row.get(1).unwrap();
+                let column_name: String = row.get(2).unwrap();
+                let column_type: String = row.get(3).unwrap();
+                let is_nullable: String = row.get(4).unwrap();
+                let field_description = format!(
+                    "({}{})",
+                    column_type,
+                    match is_nullable.as_ref() {
+                        "NO" => " NOT NULL",
+                        _ => ""
+                    }
+                );
+
+                match &current_schema {
+                    Some(node_id) if schema_name == dbitems.0.get(node_id).unwrap().data().name => {},
+                    _ => {
+                        current_table = None;
+                        current_schema = Some(
+                            dbitems.0.insert(
+                                Node::new(
+                                    DBItem{name: schema_name.to_string(), description: None}
+                                ),
+                                InsertBehavior::UnderNode(&root_node)
+                            ).unwrap()
+                        );
+                    }
+                }
+
+                match &current_table {
+                    Some(node_id) if table_name == dbitems.0.get(node_id).unwrap().data().name => {},
+                    _ => {
+                        current_table = Some(
+                            dbitems.0.insert(
+                                Node::new(
+                                    DBItem{name: table_name.to_string(), description: None}
+                                ),
+                                InsertBehavior::UnderNode(current_schema.as_ref().unwrap())
+                            ).unwrap()
+                        );
+                    }
+                }
+
+                dbitems.0.insert(
+                    Node::new(
+                        DBItem{name: column_name.to_string(), description: Some(field_description)}
+                    ),
+                    InsertBehavior::UnderNode(current_table.as_ref().unwrap())
+                ).unwrap();
+            }
+            if let Some(query) = &schema_command.query {
+                dbitems = dbitems.subtree_matching_query(&query.to_lowercase(), schema_command.regex);
+            }
+            dbitems.print();
+        },
         #[cfg(feature = "use_postgres")]
         SourceConfigCommand::Postgres(postgres_config_options) => {
-          let mut conn = establish_postgres_connection(postgres_config_options);
+          let (mut conn, _tunnel) = establish_postgres_connection(postgres_config_options);
           let mut where_parts = vec!["t.table_schema='public'"];
           let mut params:Vec<&(dyn postgres::types::ToSql + std::marker::Sync)> = vec![];
           if let Some(dbname) = &postgres_config_options.database {
@@ -1,22 +1,75 @@
 use std::io::Write;
 use std::process::Command;
 
-use crate::config;
+use crate::config::{self, Settings};
 use crate::commands;
 use crate::commands::{ApplicationArguments};
 use crate::commands::common::{SourceConfigCommandWrapper, SourceConfigCommand};
+#[cfg(feature = "use_mysql")]
+use crate::commands::common::MysqlConfigOptions;
+#[cfg(feature = "use_postgres")]
+use crate::commands::common::PostgresConfigOptions;
+#[cfg(feature = "use_mysql")]
+use crate::sources::mysql::{mysql_tunnel_target, GetMysqlConnectionParams};
+#[cfg(feature = "use_postgres")]
+use crate::sources::postgres::{postgres_tunnel_target, GetPostgresConnectionParams};
+#[cfg(any(feature = "use_mysql", feature = "use_postgres"))]
+use crate::utils::ssh::{open_tunnel_or_exit, SshTunnel};
 
-static KNOWN_SHELLS:[&str; 8] = ["default", "python", "litecli", "sqlite", "mycli", "mysql", "pgcli", "psql"];
+pub static KNOWN_SHELLS:[&str; 9] = ["default", "python", "litecli", "sqlite", "mycli", "mysql", "pgcli", "psql", "duckdb"];
 
 
 #[derive(StructOpt)]
 pub struct ShellCommand {
-    #[structopt(short = "c", long = "client", help = "select shell (client)", default_value="default",  possible_values = &KNOWN_SHELLS)]
-    pub client: String,
+    #[structopt(short = "c", long = "client", help = "select shell (client) [default: default]", possible_values = &KNOWN_SHELLS)]
+    pub client: Option<String>,
     #[structopt(subcommand)]
     pub source: SourceConfigCommandWrapper,
 }
 
+impl ShellCommand {
+
+    ///use client set in settings for source type, unless given on command line
+    pub fn update_from_settings(&mut self, settings: &Settings) {
+        if self.client.is_none() {
+            self.client = settings.shell.get_client(&self.source.0.get_type_name()).cloned();
+        }
+    }
+}
+
+
+///options pointing to local end of ssh tunnel, if ssh host is given. Password is resolved beforehand,
+///since it may be looked up by host
+#[cfg(feature = "use_mysql")]
+fn mysql_through_tunnel(mysql_config_options: &MysqlConfigOptions) -> (MysqlConfigOptions, Option<SshTunnel>) {
+    let mut options = mysql_config_options.clone();
+    let tunnel = open_tunnel_or_exit(&mysql_config_options.ssh_options, mysql_tunnel_target(mysql_config_options), &mysql_config_options.timeout);
+    if let Some(tunnel) = &tunnel {
+        options.password = mysql_config_options.get_password().map(Some);
+        options.password_options = Default::default();
+        options.host = Some("127.0.0.1".to_string());
+        options.port = Some(tunnel.local_port);
+        options.socket = None;
+        options.ssh_options = Default::default();
+    }
+    (options, tunnel)
+}
+
+///options pointing to local end of ssh tunnel, if ssh host is given. Password is resolved beforehand,
+///since it may be looked up by host
+#[cfg(feature = "use_postgres")]
+fn postgres_through_tunnel(postgres_config_options: &PostgresConfigOptions) -> (PostgresConfigOptions, Option<SshTunnel>) {
+    let mut options = postgres_config_options.clone();
+    let tunnel = open_tunnel_or_exit(&postgres_config_options.ssh_options, postgres_tunnel_target(postgres_config_options), &postgres_config_options.timeout);
+    if let Some(tunnel) = &tunnel {
+        options.password = postgres_config_options.get_password().map(Some);
+        options.password_options = Default::default();
+        options.host = Some("127.0.0.1".to_string());
+        options.port = Some(tunnel.local_port);
+        options.ssh_options = Default::default();
+    }
+    (options, tunnel)
+}
 
 pub fn create_python_virtualenv(path: &std::path::PathBuf) {
 
@@ -45,7 +98,7 @@ pub fn mysql_client(mysql_config_options: &commands::common::MysqlConfigOptions)
         cmd.arg("-P").arg(port.to_string());
     }
 
-    if let Some(password) = &mysql_config_options.password {
+    if let Some(password) = &mysql_config_options.get_password() {
         cmd.arg("-p".to_string() + password);
     }
 
@@ -71,7 +124,7 @@ pub fn mycli_client(mysql_config_options: &commands::common::MysqlConfigOptions)
         cmd.arg("-P").arg(port.to_string());
     }
 
-    if let Some(password) = &mysql_config_options.password {
+    if let Some(password) = &mysql_config_options.get_password() {
         cmd.arg("-p".to_string() + password);
     }
 
@@ -118,7 +171,7 @@ pub fn mysql_python_client(mysql_config_options: &commands::common::MysqlConfigO
     if let Some(port) =  &mysql_config_options.port {
         std::env::set_var("MYSQL_PORT", port.to_string());
     }
-    if let Some(password) = &mysql_config_options.password {
+    if let Some(password) = &mysql_config_options.get_password() {
         std::env::set_var("MYSQL_PASSWORD", password);
     }
     if let Some(database) =  &mysql_config_options.database {
@@ -198,7 +251,7 @@ pub fn postgres_python_client(postgres_config_options: &commands::common::Postgr
     if let Some(port) =  &postgres_config_options.port {
         std::env::set_var("POSTGRES_PORT", port.to_string());
     }
-    if let Some(password) = &postgres_config_options.password {
+    if let Some(password) = &postgres_config_options.get_password() {
         std::env::set_var("POSTGREs_PASSWORD", password);
     }
     if let Some(database) =  &postgres_config_options.database {
@@ -281,41 +334,71 @@ pub fn sqlite_python_client(sqlite_config_options: &commands::common::SqliteConf
 }
 
 
+#[cfg(feature = "use_duckdb")]
+pub fn duckdb_client(duckdb_config_options: &commands::common::DuckdbConfigOptions) {
+    let mut cmd = Command::new("duckdb");
+    if let Some(filename) =  &duckdb_config_options.filename {
+        cmd.arg(filename);
+    }
+
+    cmd
+        .status()
+        .expect(&format!("failed to execute duckdb ({:?})", cmd));
+}
+
+
 pub fn shell (_args: &ApplicationArguments, shell_command: &ShellCommand) {
 
+    let client = shell_command.client.as_deref().unwrap_or("default");
+
     match &shell_command.source.0 {
         #[cfg(feature = "use_mysql")]
-        SourceConfigCommand::Mysql(mysql_config_options) => 
-            match shell_command.client.as_ref() {
+        SourceConfigCommand::Mysql(mysql_config_options) => {
+            //tunnel is closed when client exits
+            let (mysql_config_options, _tunnel) = mysql_through_tunnel(mysql_config_options);
+            match client {
                 "mycli" => mycli_client(&mysql_config_options),
                 "default" | "mysql" => mysql_client(&mysql_config_options),
                 "python" => mysql_python_client(&mysql_config_options),
                 _ =>  {
-                    eprintln!("client unknown or unsuitable for given source: {}", shell_command.client);
+                    eprintln!("client unknown or unsuitable for given source: {}", client);
                     std::process::exit(1);
                 }
             }
+        },
 
         #[cfg(feature = "use_sqlite")]
         SourceConfigCommand::Sqlite(sqlite_config_options) => {
-            match shell_command.client.as_ref() {
+            match client {
                 "litecli" => litecli_client(&sqlite_config_options),
                 "default" | "sqlite" => sqlite_client(&sqlite_config_options),
                 "python" => sqlite_python_client(&sqlite_config_options),
                 _ =>  {
-                    eprintln!("client unknown or unsuitable for given source: {}", shell_command.client);
+                    eprintln!("client unknown or unsuitable for given source: {}", client);
+                    std::process::exit(1);
+                }
+            }
+        },
+        #[cfg(feature = "use_duckdb")]
+        SourceConfigCommand::Duckdb(duckdb_config_options) => {
+            match client {
+                "default" | "duckdb" => duckdb_client(&duckdb_config_options),
+                _ =>  {
+                    eprintln!("client unknown or unsuitable for given source: {}", client);
                     std::process::exit(1);
                 }
             }
         },
         #[cfg(feature = "use_postgres")]
         SourceConfigCommand::Postgres(postgres_config_options) => {
-            match shell_command.client.as_ref() {
+            //tunnel is closed when client exits
+            let (postgres_config_options, _tunnel) = postgres_through_tunnel(postgres_config_options);
+            match client {
                 "pgcli" => pgcli_client(&postgres_config_options),
                 "default" | "psql" => psql_client(&postgres_config_options),
                 "python" => postgres_python_client(&postgres_config_options),
                 _ =>  {
-                    eprintln!("client unknown or unsuitable for given source: {}", shell_command.client);
+                    eprintln!("client unknown or unsuitable for given source: {}", client);
                     std::process::exit(1);
                 }
             }
@@ -1,15 +1,252 @@
 use std::cmp::max;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use default_editor;
+#[cfg(feature = "use_mysql")]
+use mysql::prelude::Queryable;
 use regex::RegexBuilder;
+use serde_derive::Serialize;
 
 use crate::config;
 
 use crate::commands::ApplicationArguments;
-use crate::commands::common::SourceConfigCommand;
+use crate::commands::common::{source_config_from_url, SourceConfigCommand};
+#[cfg(feature = "use_mysql")]
+use crate::commands::common::MysqlConfigOptions;
+#[cfg(feature = "use_postgres")]
+use crate::commands::common::PostgresConfigOptions;
+#[cfg(feature = "use_duckdb")]
+use crate::sources::duckdb::try_establish_duckdb_connection;
+#[cfg(feature = "use_mysql")]
+use crate::sources::mysql::try_establish_mysql_connection;
+#[cfg(feature = "use_postgres")]
+use crate::sources::postgres::try_establish_postgres_connection;
+#[cfg(feature = "use_sqlite")]
+use crate::sources::sqlite::try_establish_sqlite_connection;
+use crate::utils::edit_file;
+#[cfg(feature = "use_mysql")]
+use crate::utils::mycnf::{get_mycnf_paths, group_options, read_mycnf};
+#[cfg(any(feature = "use_mysql", feature = "use_postgres"))]
+use crate::utils::password::ask_password;
+#[cfg(feature = "use_postgres")]
+use crate::utils::{pg_service::{get_pg_service_paths, read_pg_services}, pgpass::{get_pgpass_path, parse_pgpass}};
+#[cfg(feature = "use_vault")]
+use crate::utils::vault::Vault;
+
+///placeholder shown instead of passwords
+const MASKED: &str = "********";
+
+///files sources can be imported from
+const IMPORT_FORMATS: [&str; 3] = ["pgpass", "pg_service", "mycnf"];
+
+///store password given with --password in vault, if --password-vault is given too, so that it's not saved in source file.
+///Warn about saving plain text password otherwise. Password is asked for if --password has no value.
+#[cfg(any(feature = "use_mysql", feature = "use_postgres"))]
+fn protect_password(name: &str, source: &mut SourceConfigCommand) -> Result<(), String> {
+    if let Some((password, password_options)) = source.password_mut() {
+        if let Some(None) = password {
+            *password = Some(Some(ask_password(&format!("source {}", name), false)?));
+        }
+        match (password.clone().flatten().as_ref(), password_options.password_vault.as_ref()) {
+            #[cfg(feature = "use_vault")]
+            (Some(secret), Some(name)) => {
+                let mut vault = Vault::open(true)?;
+                vault.secrets.insert(name.clone(), secret.clone());
+                vault.save()?;
+                *password = None;
+            },
+            (Some(_), _) => eprintln!("Warning: password is saved in plain text, consider using --password-vault, --password-command or --password-env"),
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+///replace passwords in source definition with placeholder
+fn mask_secrets(value: &mut toml::Value) {
+    if let toml::Value::Table(table) = value {
+        for (key, value) in table.iter_mut() {
+            match value {
+                toml::Value::String(_) if key == "password" => *value = toml::Value::String(MASKED.to_string()),
+                toml::Value::Table(_) => mask_secrets(value),
+                _ => {},
+            }
+        }
+    }
+}
 
 pub fn sources_add(_args: &ApplicationArguments, _sources_command: &SourcesCommand, add_options: &SourcesAddOptions) {
-    config::save_source_config(&add_options.name, &add_options.source);
+    let source = match (&add_options.source, &add_options.url) {
+        (Some(source), None) => {
+            let mut source = source.clone();
+            source.update_from_url(true);
+            Ok(source)
+        },
+        (Some(source), Some(url)) => {
+            let mut source = source.clone();
+            source.update_from_url(false);
+            source_config_from_url(url).and_then(|url_source| source.update_from_source_config(&url_source)).map(|_| source)
+        },
+        //source type is taken from url
+        (None, url) => match url.clone().or_else(|| std::env::var("DATABASE_URL").ok()) {
+            Some(url) => source_config_from_url(&url),
+            None => Err(format!("give source type (for example: dbfish sources add {} postgres --host ...) or --url", add_options.name)),
+        },
+    };
+    #[cfg(any(feature = "use_mysql", feature = "use_postgres"))]
+    let source = source.and_then(|mut source| protect_password(&add_options.name, &mut source).map(|_| source));
+    match source {
+        Ok(source) => config::save_source_config(&add_options.name, &source),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+///source name made of characters safe in file names
+fn import_source_name(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .filter(|part| !part.is_empty() && **part != "*")
+        .map(|part| part.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' }).collect::<String>())
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+///one source per pgpass line, named after its host, port, database and user. Passwords are not copied,
+///since they are read from pgpass when connecting
+#[cfg(feature = "use_postgres")]
+fn import_pgpass(file: &Option<PathBuf>) -> Result<Vec<(String, SourceConfigCommand)>, String> {
+    let path = match file.clone().or_else(get_pgpass_path) {
+        Some(path) => path,
+        None => return Err("can't locate pgpass file, give it with --file".to_string()),
+    };
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+    let wildcard = |value: &str| if value == "*" { None } else { Some(value.to_string()) };
+    let mut sources = vec![];
+    for entry in parse_pgpass(&content) {
+        let port = if entry.port == "5432" { "" } else { entry.port.as_str() };
+        let name = import_source_name(&[&entry.host, port, &entry.database, &entry.user]);
+        if name.is_empty() {
+            continue;
+        }
+        let options = PostgresConfigOptions {
+            host: wildcard(&entry.host),
+            port: match wildcard(&entry.port) {
+                Some(port) => Some(port.parse().map_err(|_| format!("{}: invalid port: {}", path.display(), port))?),
+                None => None,
+            },
+            database: wildcard(&entry.database),
+            user: wildcard(&entry.user),
+            ..Default::default()
+        };
+        sources.push((name, SourceConfigCommand::Postgres(options)));
+    }
+    Ok(sources)
+}
+
+///one source per service, named like it
+#[cfg(feature = "use_postgres")]
+fn import_pg_service(file: &Option<PathBuf>) -> Result<Vec<(String, SourceConfigCommand)>, String> {
+    let paths = match file {
+        Some(path) => vec![path.clone()],
+        None => get_pg_service_paths(),
+    };
+    let mut sources = vec![];
+    for service in read_pg_services(&paths)? {
+        let mut options = PostgresConfigOptions::default();
+        for (key, value) in service.values.iter().cloned() {
+            if !options.set_param(&key, value).map_err(|e| format!("service {}: {}", service.name, e))? {
+                eprintln!("Warning: service {}: parameter {} is not supported, skipping it", service.name, key);
+            }
+        }
+        sources.push((import_source_name(&[&service.name]), SourceConfigCommand::Postgres(options)));
+    }
+    Ok(sources)
+}
+
+///source "client" from [client] and [mysql] groups, and one source per [clientSUFFIX] group
+///(used with mysql --defaults-group-suffix), named after suffix
+#[cfg(feature = "use_mysql")]
+fn import_mycnf(file: &Option<PathBuf>) -> Result<Vec<(String, SourceConfigCommand)>, String> {
+    let paths = match file {
+        Some(path) => vec![path.clone()],
+        None => get_mycnf_paths(),
+    };
+    let groups = read_mycnf(&paths)?;
+    let mut names = vec![("client".to_string(), vec!["client", "mysql"])];
+    for group in groups.iter() {
+        if let Some(suffix) = group.name.strip_prefix("client") {
+            let suffix = suffix.trim_start_matches(&['-', '_'][..]);
+            if !suffix.is_empty() && !names.iter().any(|(name, _)| name == suffix) {
+                names.push((suffix.to_string(), vec!["client", "mysql", group.name.as_str()]));
+            }
+        }
+    }
+    let mut sources = vec![];
+    for (name, group_names) in names {
+        let values = group_options(&groups, &group_names);
+        if values.is_empty() {
+            continue;
+        }
+        let mut options = MysqlConfigOptions::default();
+        //option files contain many settings unrelated to connecting, so unknown ones are skipped silently
+        for (key, value) in values {
+            options.set_param(&key, value).map_err(|e| format!("[{}]: {}", name, e))?;
+        }
+        sources.push((import_source_name(&[&name]), SourceConfigCommand::Mysql(options)));
+    }
+    Ok(sources)
+}
+
+pub fn sources_import(_args: &ApplicationArguments, _sources_command: &SourcesCommand, import_options: &SourcesImportOptions) {
+    let sources = match import_options.from.as_str() {
+        #[cfg(feature = "use_postgres")]
+        "pgpass" => import_pgpass(&import_options.file),
+        #[cfg(feature = "use_postgres")]
+        "pg_service" => import_pg_service(&import_options.file),
+        #[cfg(feature = "use_mysql")]
+        "mycnf" => import_mycnf(&import_options.file),
+        other => Err(format!("dbfish was compiled without support for importing from {}", other)),
+    };
+    let sources = match sources {
+        Ok(sources) => sources,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if sources.is_empty() {
+        eprintln!("No sources found");
+        return;
+    }
+    let mut imported: Vec<String> = vec![];
+    for (name, mut source) in sources {
+        let mut name = format!("{}{}", import_options.prefix.as_deref().unwrap_or(""), name);
+        //names may repeat, for example pgpass lines differing only in password or unsafe characters
+        let base_name = name.clone();
+        let mut counter = 1;
+        while imported.contains(&name) {
+            counter += 1;
+            name = format!("{}-{}", base_name, counter);
+        }
+        if !import_options.overwrite && config::get_sources_config_directory().join(&name).exists() {
+            eprintln!("Skipping {}: source already exists (use --overwrite to replace it)", name);
+            continue;
+        }
+        #[cfg(any(feature = "use_mysql", feature = "use_postgres"))]
+        if let Err(e) = protect_password(&name, &mut source) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        config::save_source_config(&name, &source);
+        println!("Imported {}", name);
+        imported.push(name);
+    }
+    if import_options.from == "pgpass" && !imported.is_empty() {
+        println!("Passwords are not copied, they are read from pgpass when connecting");
+    }
 }
 
 pub fn sources_delete(_args: &ApplicationArguments, _sources_command: &SourcesCommand, delete_options: &SourcesDeleteOptions) {
@@ -17,35 +254,113 @@ pub fn sources_delete(_args: &ApplicationArguments, _sources_command: &SourcesCo
     std::fs::remove_file(filename).unwrap();
 }
 
+///exit with error message unless source exists and new name is free
+fn check_source_names(name: &str, new_name: &str) {
+    let directory = config::get_sources_config_directory();
+    if !directory.join(name).is_file() {
+        eprintln!("Error: source {} does not exist", name);
+        std::process::exit(1);
+    }
+    if directory.join(new_name).exists() {
+        eprintln!("Error: source {} already exists", new_name);
+        std::process::exit(1);
+    }
+}
+
+///point sources extending renamed one to its new name
+fn update_extends(name: &str, new_name: &str) -> Result<(), String> {
+    let directory = config::get_sources_config_directory();
+    for entry in std::fs::read_dir(&directory).map_err(|e| format!("can't read {}: {}", directory.display(), e))? {
+        let path = entry.map_err(|e| format!("can't read {}: {}", directory.display(), e))?.path();
+        if !path.is_file() {
+            continue;
+        }
+        //broken files are reported when sources are read
+        let mut toml_value = match config::toml_from_file(&path) {
+            Ok(toml_value) => toml_value,
+            Err(_) => continue,
+        };
+        if let toml::Value::Table(table) = &mut toml_value {
+            if table.get("extends").and_then(|extends| extends.as_str()) == Some(name) {
+                table.insert("extends".to_string(), toml::Value::String(new_name.to_string()));
+                config::write_private_file(&path, toml::to_string(&toml_value).unwrap().as_bytes())
+                    .map_err(|e| format!("can't write {}: {}", path.display(), e))?;
+                println!("Updated extends of {}", path.file_name().unwrap().to_string_lossy());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn sources_rename(_args: &ApplicationArguments, _sources_command: &SourcesCommand, rename_options: &SourcesRenameOptions) {
+    check_source_names(&rename_options.name, &rename_options.new_name);
+    let directory = config::get_sources_config_directory();
+    let renamed = std::fs::rename(directory.join(&rename_options.name), directory.join(&rename_options.new_name))
+        .map_err(|e| format!("can't rename source: {}", e))
+        .and_then(|_| update_extends(&rename_options.name, &rename_options.new_name));
+    if let Err(e) = renamed {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+///copy source file as it is, so that copy extends the same source as original
+pub fn sources_copy(_args: &ApplicationArguments, _sources_command: &SourcesCommand, copy_options: &SourcesCopyOptions) {
+    check_source_names(&copy_options.name, &copy_options.new_name);
+    let directory = config::get_sources_config_directory();
+    let copied = std::fs::read(directory.join(&copy_options.name))
+        .and_then(|content| config::write_private_file(&directory.join(&copy_options.new_name), &content));
+    if let Err(e) = copied {
+        eprintln!("Error: can't copy source: {}", e);
+        std::process::exit(1);
+    }
+}
+
+pub fn sources_validate(_args: &ApplicationArguments, _sources_command: &SourcesCommand, _validate_options: &SourcesValidateOptions) {
+    let sources = config::read_sources();
+    let mut broken = 0;
+    for (name, source) in sources.iter() {
+        match source {
+            Ok(_) => println!("{}: OK", name),
+            Err(e) => {
+                println!("{}: {}", name, e);
+                broken += 1;
+            },
+        }
+    }
+    if broken > 0 {
+        eprintln!("{} of {} sources have errors", broken, sources.len());
+        std::process::exit(1);
+    }
+}
+
 pub fn sources_edit(_args: &ApplicationArguments, _sources_command: &SourcesCommand, edit_options: &SourcesEditOptions) {
     let filename = config::get_sources_config_directory().join(edit_options.name.clone());
     if filename.exists() {
-        match default_editor::get() {
-            Ok(editor) => {
-                std::process::Command::new(editor)
-                    .args(&[filename])
-                    .status()
-                    .expect("could not run your text editor");
-            },
-            Err(error) => {
-                eprintln!("Error: Could not figure out text editor to use: {}", error);
-            }
-        };
+        if let Err(error) = edit_file(&filename) {
+            eprintln!("Error: {}", error);
+        }
     } else {
         eprintln!("Error: File {} does not exist", filename.to_str().unwrap());
         std::process::exit(1);
     }
 }
 
+///saved sources with names matching pattern (regular expression, case insensitive), all when there is none
+fn get_matching_sources(pattern: &Option<String>) -> Vec<(String, SourceConfigCommand)> {
+    let sources = config::get_sources_list();
+    match pattern {
+        Some(pattern) => {
+            let re = RegexBuilder::new(pattern.as_ref()).case_insensitive(true).build().unwrap();
+            sources.into_iter().filter(|(name, _src)| re.is_match(name)).collect()
+        },
+        None => sources,
+    }
+}
+
 pub fn sources_list(_args: &ApplicationArguments, _sources_command: &SourcesCommand, list_options: &SourcesListOptions) {
 
-    let mut sources = config::get_sources_list();
-    if let Some(ref pattern) = list_options.pattern {
-        let re = RegexBuilder::new(pattern.as_ref()).case_insensitive(true).build().unwrap();
-        sources = sources.into_iter().filter(|(name, _src)|{
-            re.is_match(name)
-        }).collect();
-    }
+    let sources = get_matching_sources(&list_options.pattern);
     let mut max_source_length = 0;
     sources.iter().for_each(|src| max_source_length = max(src.0.len(),max_source_length));
     for source in sources {
@@ -55,17 +370,157 @@ pub fn sources_list(_args: &ApplicationArguments, _sources_command: &SourcesComm
 
 pub fn sources_show(_args: &ApplicationArguments, _sources_command: &SourcesCommand, show_options: &SourcesShowOptions) {
 
-    let mut sources = config::get_sources_list();
-    if let Some(ref pattern) = show_options.pattern {
-        let re = RegexBuilder::new(pattern.as_ref()).case_insensitive(true).build().unwrap();
-        sources = sources.into_iter().filter(|(name, _src)|{
-            re.is_match(name)
-        }).collect();
-    }
+    let sources = get_matching_sources(&show_options.pattern);
     let mut max_source_length = 0;
     sources.iter().for_each(|src| max_source_length = max(src.0.len(),max_source_length));
     for (name, source) in sources {
-        println!("{}\n{}", name, source.to_full_toml())
+        let mut toml_value = source.to_full_toml();
+        if !show_options.reveal {
+            mask_secrets(&mut toml_value);
+        }
+        println!("{}\n{}", name, toml_value)
+    }
+}
+
+
+///outcome of connecting to source, serialized for --json
+#[derive(Serialize)]
+struct SourceTestResult {
+    name: String,
+    #[serde(rename = "type")]
+    source_type: String,
+    ok: bool,
+    version: Option<String>,
+    connect_ms: Option<f64>,
+    latency_ms: Option<f64>,
+    error: Option<String>,
+}
+
+///file databases would be created when opened, so missing file is reported instead
+#[cfg(any(feature = "use_sqlite", feature = "use_duckdb"))]
+fn check_database_file(filename: &Option<String>) -> Result<(), String> {
+    match filename {
+        Some(filename) if !Path::new(filename).exists() => Err(format!("file {} does not exist", filename)),
+        _ => Ok(()),
+    }
+}
+
+///connect timeout used when neither source nor command line gives one, so that unreachable hosts don't block the test
+const DEFAULT_TEST_TIMEOUT: u64 = 10;
+
+///connect to source and query its version. Returns version, time taken to connect and query round trip time.
+///Password is never asked for, so that it can run unattended
+fn check_source(source: &SourceConfigCommand, timeout: &Option<u64>) -> Result<(String, Duration, Duration), String> {
+    let started = Instant::now();
+    match source {
+        #[cfg(feature = "use_mysql")]
+        SourceConfigCommand::Mysql(options) => {
+            let mut options = options.clone();
+            options.timeout = timeout.or(options.timeout).or(Some(DEFAULT_TEST_TIMEOUT));
+            let (mut connection, _tunnel, _temp_files) = try_establish_mysql_connection(&options, false)?;
+            let connected = started.elapsed();
+            let started = Instant::now();
+            let version: Option<String> = connection.query_first("select version()").map_err(|e| e.to_string())?;
+            Ok((version.unwrap_or_default(), connected, started.elapsed()))
+        },
+        #[cfg(feature = "use_postgres")]
+        SourceConfigCommand::Postgres(options) => {
+            let mut options = options.clone();
+            options.timeout = timeout.or(options.timeout).or(Some(DEFAULT_TEST_TIMEOUT));
+            let (mut client, _tunnel) = try_establish_postgres_connection(&options, false)?;
+            let connected = started.elapsed();
+            let started = Instant::now();
+            let row = client.query_one("select current_setting('server_version')", &[]).map_err(|e| e.to_string())?;
+            Ok((row.get(0), connected, started.elapsed()))
+        },
+        #[cfg(feature = "use_sqlite")]
+        SourceConfigCommand::Sqlite(options) => {
+            check_database_file(&options.filename)?;
+            let connection = try_establish_sqlite_connection(options)?;
+            let connected = started.elapsed();
+            let started = Instant::now();
+            let mut statement = connection.prepare("select sqlite_version()").map_err(|e| e.to_string())?;
+            statement.next().map_err(|e| e.to_string())?;
+            let version = statement.read::<String>(0).map_err(|e| e.to_string())?;
+            Ok((version, connected, started.elapsed()))
+        },
+        #[cfg(feature = "use_duckdb")]
+        SourceConfigCommand::Duckdb(options) => {
+            check_database_file(&options.filename)?;
+            let connection = try_establish_duckdb_connection(options)?;
+            let connected = started.elapsed();
+            let started = Instant::now();
+            let version: String = connection.query_row("select version()", [], |row| row.get(0)).map_err(|e| e.to_string())?;
+            Ok((version, connected, started.elapsed()))
+        },
+    }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 10000.0).round() / 10.0
+}
+
+fn print_test_results_table(results: &[SourceTestResult]) {
+    let format_ms = |ms: Option<f64>| ms.map(|ms| format!("{:.1} ms", ms)).unwrap_or_default();
+    let mut rows = vec![["source", "type", "status", "version", "connect", "latency", "error"].iter().map(|title| title.to_string()).collect::<Vec<String>>()];
+    for result in results {
+        rows.push(vec![
+            result.name.clone(),
+            result.source_type.clone(),
+            if result.ok { "OK" } else { "FAIL" }.to_string(),
+            result.version.clone().unwrap_or_default(),
+            format_ms(result.connect_ms),
+            format_ms(result.latency_ms),
+            //multiline errors (failed init queries) are shown in one line
+            result.error.as_deref().unwrap_or("").split_whitespace().collect::<Vec<&str>>().join(" "),
+        ]);
+    }
+    let mut widths = vec![0; rows[0].len()];
+    for row in rows.iter() {
+        for (width, value) in widths.iter_mut().zip(row.iter()) {
+            *width = max(*width, value.chars().count());
+        }
+    }
+    for row in rows {
+        let line = row.iter().zip(widths.iter()).map(|(value, width)| format!("{:width$}", value, width=width)).collect::<Vec<String>>().join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+pub fn sources_test(_args: &ApplicationArguments, _sources_command: &SourcesCommand, test_options: &SourcesTestOptions) {
+    #[cfg(not(feature = "use_json"))]
+    if test_options.json {
+        eprintln!("Error: dbfish was compiled without json support");
+        std::process::exit(1);
+    }
+    let sources = get_matching_sources(&test_options.pattern);
+    if sources.is_empty() {
+        eprintln!("Error: no sources found");
+        std::process::exit(1);
+    }
+    let results: Vec<SourceTestResult> = sources
+        .iter()
+        .map(|(name, source)| {
+            let checked = check_source(source, &test_options.timeout);
+            SourceTestResult {
+                name: name.clone(),
+                source_type: source.get_type_name(),
+                ok: checked.is_ok(),
+                version: checked.as_ref().ok().map(|(version, _, _)| version.clone()),
+                connect_ms: checked.as_ref().ok().map(|(_, connect, _)| milliseconds(*connect)),
+                latency_ms: checked.as_ref().ok().map(|(_, _, latency)| milliseconds(*latency)),
+                error: checked.err(),
+            }
+        })
+        .collect();
+    if test_options.json {
+        #[cfg(feature = "use_json")]
+        println!("{}", serde_json::to_string_pretty(&results).unwrap());
+    } else {
+        print_test_results_table(&results);
+    }
+    if results.iter().any(|result| !result.ok) {
+        std::process::exit(1);
     }
 }
 
@@ -74,10 +529,15 @@ pub fn sources_show(_args: &ApplicationArguments, _sources_command: &SourcesComm
 pub fn sources(args: &ApplicationArguments, sources_command: &SourcesCommand) {
     match &sources_command.command {
         SourcesSubCommand::Add(add_options) => sources_add(&args, &sources_command, &add_options),
+        SourcesSubCommand::Copy(copy_options) => sources_copy(&args, &sources_command, &copy_options),
         SourcesSubCommand::Delete(delete_options) => sources_delete(&args, &sources_command, &delete_options),
         SourcesSubCommand::Edit(edit_options) => sources_edit(&args, &sources_command, &edit_options),
+        SourcesSubCommand::Import(import_options) => sources_import(&args, &sources_command, &import_options),
         SourcesSubCommand::List(list_options) => sources_list(&args, &sources_command, &list_options),
+        SourcesSubCommand::Rename(rename_options) => sources_rename(&args, &sources_command, &rename_options),
         SourcesSubCommand::Show(show_options) => sources_show(&args, &sources_command, &show_options),
+        SourcesSubCommand::Test(test_options) => sources_test(&args, &sources_command, &test_options),
+        SourcesSubCommand::Validate(validate_options) => sources_validate(&args, &sources_command, &validate_options),
     };
 }
 
@@ -93,26 +553,51 @@ pub enum SourcesSubCommand {
     #[structopt(name = "add", about="add source")]
     #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
     Add(SourcesAddOptions),
+    #[structopt(name = "copy", about="copy source under new name")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Copy(SourcesCopyOptions),
     #[structopt(name = "delete", about="delete source")]
     #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
     Delete(SourcesDeleteOptions),
     #[structopt(name = "edit", about="edit source definition")]
     #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
     Edit(SourcesEditOptions),
+    #[structopt(name = "import", about="create sources from ~/.pgpass, pg_service.conf or ~/.my.cnf")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Import(SourcesImportOptions),
     #[structopt(name = "list", about="list sources")]
     #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
     List(SourcesListOptions),
+    #[structopt(name = "rename", about="rename source, updating sources that extend it")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Rename(SourcesRenameOptions),
     #[structopt(name = "show", about="show source details")]
     #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
     Show(SourcesShowOptions),
+    #[structopt(name = "test", about="connect to sources and report whether it works, server version and latency")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Test(SourcesTestOptions),
+    #[structopt(name = "validate", about="check source files, reporting file, line and key of errors")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Validate(SourcesValidateOptions),
 }
 
 #[derive(Clone, Debug, StructOpt)]
 pub struct SourcesAddOptions {
     #[structopt(help = "source name")]
     pub name: String,
+    #[structopt(long = "url", help = "connection url (postgres://, mysql:// or sqlite://), used instead of source type. DATABASE_URL is used when neither is given")]
+    pub url: Option<String>,
     #[structopt(subcommand)]
-    pub source: SourceConfigCommand,
+    pub source: Option<SourceConfigCommand>,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct SourcesCopyOptions {
+    #[structopt(help = "source name")]
+    pub name: String,
+    #[structopt(help = "name of copy")]
+    pub new_name: String,
 }
 
 #[derive(Clone, Debug, StructOpt)]
@@ -127,14 +612,49 @@ pub struct SourcesEditOptions {
     pub name: String,
 }
 
+#[derive(Clone, Debug, StructOpt)]
+pub struct SourcesImportOptions {
+    #[structopt(long = "from", help = "file format", possible_values = &IMPORT_FORMATS)]
+    pub from: String,
+    #[structopt(long = "file", help = "file to import, instead of default location", parse(from_os_str))]
+    pub file: Option<PathBuf>,
+    #[structopt(long = "prefix", help = "prefix added to names of imported sources")]
+    pub prefix: Option<String>,
+    #[structopt(long = "overwrite", help = "replace existing sources with the same names")]
+    pub overwrite: bool,
+}
+
 #[derive(Clone, Debug, StructOpt)]
 pub struct SourcesListOptions {
     #[structopt(help = "pattern to search for (using regular expression)")]
     pub pattern: Option<String>,
 }
 
+#[derive(Clone, Debug, StructOpt)]
+pub struct SourcesRenameOptions {
+    #[structopt(help = "source name")]
+    pub name: String,
+    #[structopt(help = "new source name")]
+    pub new_name: String,
+}
+
 #[derive(Clone, Debug, StructOpt)]
 pub struct SourcesShowOptions {
     #[structopt(help = "pattern to search for (using regular expression)")]
     pub pattern: Option<String>,
+    #[structopt(long = "reveal", help = "show passwords instead of masking them")]
+    pub reveal: bool,
 }
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct SourcesTestOptions {
+    #[structopt(help = "pattern to search for (using regular expression)")]
+    pub pattern: Option<String>,
+    #[structopt(long = "json", help = "print results as json")]
+    pub json: bool,
+    #[structopt(long = "timeout", help = "connect timeout in seconds (default: one from source, or 10)")]
+    pub timeout: Option<u64>,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct SourcesValidateOptions {}
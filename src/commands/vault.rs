@@ -0,0 +1,104 @@
+use std::io::BufRead;
+
+use atty;
+use rpassword;
+
+use crate::commands::ApplicationArguments;
+use crate::utils::vault::Vault;
+
+
+fn open_vault(create: bool) -> Vault {
+    match Vault::open(create) {
+        Ok(vault) => vault,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn save_vault(vault: &Vault) {
+    if let Err(e) = vault.save() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+///secret is asked for on terminal, or read from first line of stdin when it's not a terminal
+fn read_secret(name: &str) -> String {
+    let secret = if atty::is(atty::Stream::Stdin) {
+        rpassword::prompt_password(format!("Secret for {}: ", name)).map_err(|e| e.to_string())
+    } else {
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line).map(|_| line.trim_end_matches(&['\r', '\n'][..]).to_string()).map_err(|e| e.to_string())
+    };
+    match secret {
+        Ok(secret) => secret,
+        Err(e) => {
+            eprintln!("Error: can't read secret: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+pub fn vault_set(_args: &ApplicationArguments, set_options: &VaultSetOptions) {
+    let mut vault = open_vault(true);
+    let secret = read_secret(&set_options.name);
+    vault.secrets.insert(set_options.name.clone(), secret);
+    save_vault(&vault);
+}
+
+pub fn vault_delete(_args: &ApplicationArguments, delete_options: &VaultDeleteOptions) {
+    let mut vault = open_vault(false);
+    if vault.secrets.remove(&delete_options.name).is_none() {
+        eprintln!("Error: no secret named {} in vault", delete_options.name);
+        std::process::exit(1);
+    }
+    save_vault(&vault);
+}
+
+pub fn vault_list(_args: &ApplicationArguments) {
+    let vault = open_vault(false);
+    for name in vault.secrets.keys() {
+        println!("{}", name);
+    }
+}
+
+pub fn vault(args: &ApplicationArguments, vault_command: &VaultCommand) {
+    match &vault_command.command {
+        VaultSubCommand::Set(set_options) => vault_set(&args, &set_options),
+        VaultSubCommand::Delete(delete_options) => vault_delete(&args, &delete_options),
+        VaultSubCommand::List => vault_list(&args),
+    };
+}
+
+#[derive(Clone, StructOpt)]
+pub struct VaultCommand {
+    #[structopt(subcommand)]
+    pub command: VaultSubCommand,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub enum VaultSubCommand {
+    #[structopt(name = "set", about="add or replace secret (read from terminal or stdin)")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Set(VaultSetOptions),
+    #[structopt(name = "delete", about="delete secret")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    Delete(VaultDeleteOptions),
+    #[structopt(name = "list", about="list names of stored secrets")]
+    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
+    List,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct VaultSetOptions {
+    #[structopt(help = "secret name, used in sources with --password-vault NAME")]
+    pub name: String,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct VaultDeleteOptions {
+    #[structopt(help = "secret name")]
+    pub name: String,
+}
@@ -3,10 +3,15 @@ use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
 use lazy_static::lazy_static;
+use serde_derive::Deserialize;
 use toml;
 use dirs::home_dir;
 
+use crate::commands::UseColor;
 use crate::commands::common::SourceConfigCommand;
+#[cfg(feature = "use_csv")]
+use crate::commands::export::parse_csv_character;
+use crate::commands::shell::KNOWN_SHELLS;
 
 
 lazy_static!{
@@ -21,13 +26,56 @@ lazy_static!{
 }
 
 
-pub fn toml_from_file(filename: &Path) -> std::io::Result<toml::Value> {
-    let mut file = std::fs::File::open(filename)?;
+pub fn toml_from_file(filename: &Path) -> Result<toml::Value, String> {
+    let mut file = std::fs::File::open(filename).map_err(|e| format!("{}: {}", filename.display(), e))?;
     let mut s = String::new();
-    file.read_to_string(&mut s)?;
-    Ok(s.parse::<toml::Value>().unwrap())
+    file.read_to_string(&mut s).map_err(|e| format!("{}: {}", filename.display(), e))?;
+    s.parse::<toml::Value>().map_err(|e| format!("{}: {}", filename.display(), e))
 }
 
+///line (counting from 1) where key is set, key of option is prefixed with its section name (postgres.host)
+pub fn find_key_line(content: &str, key: &str) -> Option<usize> {
+    let (section, name) = match key.rsplit_once('.') {
+        Some((section, name)) => (Some(section), name),
+        None => (None, key),
+    };
+    let mut current_section = None;
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            current_section = header.split(']').next().map(|header| header.trim());
+            //sections are top level keys
+            if section.is_none() && current_section == Some(name) {
+                return Some(line_number + 1);
+            }
+        } else if current_section == section {
+            if let Some((line_key, _)) = line.split_once('=') {
+                if line_key.trim().trim_matches('"') == name {
+                    return Some(line_number + 1);
+                }
+            }
+        }
+    }
+    None
+}
+
+///source from file, problems are reported with file name, line and key
+pub fn read_source_file(filename: &Path) -> Result<SourceConfigCommand, String> {
+    read_source_file_extending(filename, &mut vec![])
+}
+
+//bases are names of sources already extended, to detect cycles
+pub fn read_source_file_extending(filename: &Path, bases: &mut Vec<String>) -> Result<SourceConfigCommand, String> {
+    let content = std::fs::read_to_string(filename).map_err(|e| format!("{}: {}", filename.display(), e))?;
+    let toml_value = content.parse::<toml::Value>().map_err(|e| format!("{}: {}", filename.display(), e))?;
+    SourceConfigCommand::from_toml_extending(&toml_value, bases).map_err(|e| match e.key {
+        Some(key) => match find_key_line(&content, &key) {
+            Some(line) => format!("{}:{}: {}: {}", filename.display(), line, key, e.message),
+            None => format!("{}: {}: {}", filename.display(), key, e.message),
+        },
+        None => format!("{}: {}", filename.display(), e.message),
+    })
+}
 
 pub fn get_config_directory() -> PathBuf {
     home_dir().unwrap().join(".dbfish")
@@ -37,29 +85,47 @@ pub fn get_sources_config_directory() -> PathBuf {
     home_dir().unwrap().join(".dbfish").join("sources")
 }
 
-pub fn get_sources_list() -> Vec<(String, SourceConfigCommand)> {
+///all source files, sorted by name, with their sources or problems found in them
+pub fn read_sources() -> Vec<(String, Result<SourceConfigCommand, String>)> {
     let dirname = get_sources_config_directory();
-    let mut entries = if std::path::Path::new(&dirname).exists() {
-        std::fs::read_dir(&dirname)
-            .expect(&format!("could not read directory: {:?}", &dirname))
-            .map(|entry| entry.expect(&format!("could not process path: {:?}", &dirname)))
-            .filter(|entry| !entry.file_type().unwrap().is_dir())
-            .map(|entry| {
-                let name = entry
-                    .file_name()
-                    .into_string()
-                    .expect(&format!("could not process file: {:?}", entry.file_name()));
-                let toml_value = toml_from_file(&entry.path()).expect(&format!("could not parse toml file: {:?}", name));
-                let source_config_command = SourceConfigCommand::from_toml(&toml_value);
-                (name, source_config_command)
-            }).collect()
+    //problems with directory itself are reported as entries too, so that they are shown instead of panicking
+    let mut entries = if !std::path::Path::new(&dirname).exists() {
+        vec![]
     } else {
-            vec![]
+        match std::fs::read_dir(&dirname) {
+            Ok(dir) => dir
+                .filter_map(|entry| match entry {
+                    Ok(entry) if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) => None,
+                    Ok(entry) => Some(match entry.file_name().into_string() {
+                        Ok(name) => (name, read_source_file(&entry.path())),
+                        Err(name) => (name.to_string_lossy().to_string(), Err("file name is not valid UTF-8".to_string())),
+                    }),
+                    Err(e) => Some((dirname.display().to_string(), Err(format!("could not read directory entry: {}", e)))),
+                }).collect(),
+            Err(e) => vec![(dirname.display().to_string(), Err(format!("could not read directory: {}", e)))],
+        }
     };
-    entries.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()) );
+    entries.sort_by(|a: &(String, _), b: &(String, _)| a.0.to_lowercase().cmp(&b.0.to_lowercase()) );
     entries
 }
 
+///sources that can be used, broken ones are skipped (see warn_about_broken_sources)
+pub fn get_sources_list() -> Vec<(String, SourceConfigCommand)> {
+    read_sources()
+        .into_iter()
+        .filter_map(|(name, source)| source.ok().map(|source| (name, source)))
+        .collect()
+}
+
+///warn about sources skipped because of errors in their files
+pub fn warn_about_broken_sources() {
+    for (name, source) in read_sources() {
+        if let Err(e) = source {
+            eprintln!("Warning: skipping source {}: {}", name, e);
+        }
+    }
+}
+
 pub fn ensure_config_directory_exists() {
     if !get_config_directory().exists() {
         std::fs::create_dir(&get_config_directory()).unwrap();
@@ -69,6 +135,20 @@ pub fn ensure_config_directory_exists() {
     }
 }
 
+///write file readable only by its owner, since it may contain passwords
+pub fn write_private_file(filename: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(filename)?;
+    //mode is only used for new files
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(content)?;
+    file.flush()
+}
+
 pub fn save_source_config(name: &str, source: &SourceConfigCommand) {
     ensure_config_directory_exists();
     let filename = home_dir()
@@ -78,7 +158,193 @@ pub fn save_source_config(name: &str, source: &SourceConfigCommand) {
         .join(name);
 
     let toml_content =  source.to_full_toml();
-    let mut file = std::fs::File::create(filename).unwrap();
-    file.write_all(toml::to_string(&toml_content).unwrap().as_bytes()).unwrap();
-    file.flush().unwrap();
+    write_private_file(&filename, toml::to_string(&toml_content).unwrap().as_bytes()).unwrap();
+}
+
+
+///defaults from config file, each of them can be overridden for given source in [sources.NAME]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    pub batch_size: Option<u64>,
+    pub color: Option<String>,
+    pub verbose: Option<bool>,
+    pub truncate: Option<u64>,
+    #[serde(default)]
+    pub shell: ShellSettings,
+    #[serde(default)]
+    pub csv: CsvSettings,
+    #[serde(default)]
+    pub json: JsonSettings,
+    #[serde(default)]
+    pub sources: HashMap<String, Settings>,
+}
+
+///default shell client for each source type
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ShellSettings {
+    pub mysql: Option<String>,
+    pub postgres: Option<String>,
+    pub sqlite: Option<String>,
+    pub duckdb: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CsvSettings {
+    pub delimiter: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct JsonSettings {
+    pub indent: Option<u16>,
+}
+
+impl ShellSettings {
+
+    pub fn get_client(&self, source_type: &str) -> Option<&String> {
+        match source_type {
+            "mysql" => self.mysql.as_ref(),
+            "postgres" => self.postgres.as_ref(),
+            "sqlite" => self.sqlite.as_ref(),
+            "duckdb" => self.duckdb.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl Settings {
+
+    ///settings for given source, values set for it take precedence over global ones
+    pub fn for_source(&self, name: &str) -> Settings {
+        let mut settings = self.sources.get(name).cloned().unwrap_or_default();
+        settings.update_from(self);
+        settings
+    }
+
+    //fill values not set from other settings
+    fn update_from(&mut self, other: &Settings) {
+        self.batch_size = self.batch_size.or(other.batch_size);
+        self.color = self.color.take().or_else(|| other.color.clone());
+        self.verbose = self.verbose.or(other.verbose);
+        self.truncate = self.truncate.or(other.truncate);
+        self.shell.mysql = self.shell.mysql.take().or_else(|| other.shell.mysql.clone());
+        self.shell.postgres = self.shell.postgres.take().or_else(|| other.shell.postgres.clone());
+        self.shell.sqlite = self.shell.sqlite.take().or_else(|| other.shell.sqlite.clone());
+        self.shell.duckdb = self.shell.duckdb.take().or_else(|| other.shell.duckdb.clone());
+        self.csv.delimiter = self.csv.delimiter.take().or_else(|| other.csv.delimiter.clone());
+        self.json.indent = self.json.indent.or(other.json.indent);
+    }
+
+    ///check values which can't be checked by their types, errors are returned as key and message
+    pub fn validate(&self) -> Result<(), (String, String)> {
+        self.validate_values("")?;
+        for (name, settings) in self.sources.iter() {
+            let prefix = format!("sources.{}.", name);
+            if !settings.sources.is_empty() {
+                return Err((format!("{}sources", prefix), "sources can't be nested".to_string()));
+            }
+            settings.validate_values(&prefix)?;
+        }
+        Ok(())
+    }
+
+    fn validate_values(&self, prefix: &str) -> Result<(), (String, String)> {
+        if let Some(color) = &self.color {
+            color.parse::<UseColor>().map_err(|_| (format!("{}color", prefix), format!("expected one of: {}", UseColor::variants().join(", ").to_lowercase())))?;
+        }
+        #[cfg(feature = "use_csv")]
+        if let Some(delimiter) = &self.csv.delimiter {
+            parse_csv_character(delimiter).map_err(|e| (format!("{}csv.delimiter", prefix), e))?;
+        }
+        let clients = [("mysql", &self.shell.mysql), ("postgres", &self.shell.postgres), ("sqlite", &self.shell.sqlite), ("duckdb", &self.shell.duckdb)];
+        for (source_type, client) in clients.iter() {
+            if let Some(client) = client {
+                if !KNOWN_SHELLS.contains(&client.as_str()) {
+                    return Err((format!("{}shell.{}", prefix, source_type), format!("unknown client {}, expected one of: {}", client, KNOWN_SHELLS.join(", "))));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+///$DBFISH_CONFIG if set, otherwise ~/.dbfish/config.toml or dbfish/config.toml in $XDG_CONFIG_HOME (~/.config), whichever exists
+pub fn get_settings_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("DBFISH_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let xdg_config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| home_dir().map(|home| home.join(".config")));
+    std::iter::once(get_config_directory().join("config.toml"))
+        .chain(xdg_config_home.map(|path| path.join("dbfish").join("config.toml")))
+        .find(|path| path.is_file())
+}
+
+///settings from file, problems are reported with file name, line and key
+pub fn read_settings_file(filename: &Path) -> Result<Settings, String> {
+    let content = std::fs::read_to_string(filename).map_err(|e| format!("{}: {}", filename.display(), e))?;
+    let settings: Settings = toml::from_str(&content).map_err(|e| format!("{}: {}", filename.display(), e))?;
+    settings.validate().map_err(|(key, message)| match find_key_line(&content, &key) {
+        Some(line) => format!("{}:{}: {}: {}", filename.display(), line, key, message),
+        None => format!("{}: {}: {}", filename.display(), key, message),
+    })?;
+    Ok(settings)
+}
+
+///settings from config file, broken file is ignored with warning
+pub fn read_settings() -> Settings {
+    match get_settings_path() {
+        Some(path) => read_settings_file(&path).unwrap_or_else(|e| {
+            eprintln!("Warning: ignoring config file {}", e);
+            Settings::default()
+        }),
+        None => Settings::default(),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{find_key_line, Settings};
+    use crate::commands::common::{SourceConfigCommand, SourceConfigError};
+
+    #[test]
+    fn test_find_key_line() {
+        let content = "type = \"postgres\"\n\n[postgres]\nhost = \"db\"\n  port=5432\n";
+        assert_eq!(find_key_line(content, "type"), Some(1));
+        assert_eq!(find_key_line(content, "postgres"), Some(3));
+        assert_eq!(find_key_line(content, "postgres.port"), Some(5));
+        assert_eq!(find_key_line(content, "postgres.type"), None);
+        let content = "color = \"no\"\n[sources.prod.csv]\ndelimiter = \";\"\n";
+        assert_eq!(find_key_line(content, "sources.prod.csv.delimiter"), Some(3));
+    }
+
+    #[test]
+    fn test_settings_for_source() {
+        let settings: Settings = toml::from_str("batch_size = 100\ntruncate = 20\n[json]\nindent = 2\n[sources.prod]\nbatch_size = 1000\n[sources.prod.json]\nindent = 8\n").unwrap();
+        let prod = settings.for_source("prod");
+        assert_eq!((prod.batch_size, prod.truncate, prod.json.indent), (Some(1000), Some(20), Some(8)));
+        let other = settings.for_source("other");
+        assert_eq!((other.batch_size, other.truncate, other.json.indent), (Some(100), Some(20), Some(2)));
+        assert!(toml::from_str::<Settings>("[sources.prod]\nbatch_sise = 1000\n").is_err());
+        let settings: Settings = toml::from_str("[sources.prod.shell]\npostgres = \"pgsql\"\n").unwrap();
+        assert_eq!(settings.validate().unwrap_err().0, "sources.prod.shell.postgres");
+    }
+
+    #[cfg(feature = "use_postgres")]
+    #[test]
+    fn test_source_errors() {
+        let error = |content: &str| SourceConfigCommand::from_toml(&content.parse::<toml::Value>().unwrap()).err();
+        assert_eq!(error("type = \"postgres\"\n[postgres]\nhost = \"db\"\nport = 5432\n"), None);
+        assert_eq!(error("type = \"postgres\"\n[postgres]\nhots = \"db\"\n"), Some(SourceConfigError::new(Some("postgres.hots"), "unknown key")));
+        assert_eq!(error("[postgres]\nhost = \"db\"\n"), Some(SourceConfigError::new(None, "missing key: type")));
+        assert!(matches!(error("type = \"postgres\"\n[postgres]\nport = \"db\"\n"), Some(SourceConfigError { key: Some(key), .. }) if key == "postgres.port"));
+    }
+
 }
@@ -1,6 +1,6 @@
 use chrono;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ColumnType {
     U64,
     I64,
@@ -56,12 +56,23 @@ pub struct ColumnInfo {
     pub data_type: ColumnType,
 }
 
+///details of a single query, available once all its rows were processed
+#[derive(Clone, Debug)]
+pub struct ResultsInfo {
+    pub source_name: String,
+    pub query: String,
+    pub started: chrono::DateTime<chrono::Utc>,
+    pub duration: std::time::Duration,
+    pub row_count: u64,
+}
+
 
 pub trait DataDestination
 {
     fn prepare(&mut self);
     fn prepare_for_results(&mut self, result_iterator: &dyn DataSourceBatchIterator);
     fn add_rows(&mut self, rows: &[Row]);
+    fn finish_results(&mut self, _results_info: &ResultsInfo) {}
     fn close(&mut self);
 }
 
@@ -76,7 +87,12 @@ pub trait DataSourceBatchIterator {
 pub trait DataSourceConnection<'i, I>
 where I: DataSourceBatchIterator + 'i
 {
-    fn batch_iterator(&'i mut self, batch_size: u64) -> I;
+    ///run sql statements that don't return results
+    fn execute(&mut self, _sql: &str) {
+        eprintln!("Error: this source does not run sql statements");
+        std::process::exit(1);
+    }
+    fn batch_iterator(&'i mut self, query: &str, batch_size: u64) -> I;
 }
 
 
@@ -1,51 +1,65 @@
+use std::io::Write;
+
 use csv;
 use termcolor;
 
 use crate::commands::export::CSVDestinationOptions;
-use crate::definitions::{Value, Row, DataSourceBatchIterator, DataDestination};
+use crate::definitions::{Row, DataSourceBatchIterator, DataDestination};
+use crate::utils::encoding::{EncodingWriter, TextEncoding};
 use crate::utils::fileorstdout::FileOrStdout;
-use crate::utils::{escape_binary_data, truncate_text_with_note};
+use crate::utils::formatter::ValueFormatter;
 
 pub struct CSVDestination {
-    csv_writer: csv::Writer<FileOrStdout>,
-    truncate: Option<u64>,
+    csv_writer: csv::Writer<EncodingWriter<FileOrStdout>>,
+    formatter: ValueFormatter,
     no_headers: bool,
 }
 
 impl CSVDestination 
 {
     pub fn init(csv_options: &CSVDestinationOptions) -> CSVDestination {
-        let csv_writer = csv::Writer::from_writer(match csv_options.filename.as_str() {
+        let mut writer = match csv_options.filename.as_str() {
             "-" => FileOrStdout::ColorStdout(termcolor::StandardStream::stdout(termcolor::ColorChoice::Never)),
             _ => FileOrStdout::File(std::fs::File::create(csv_options.filename.to_string()).unwrap())
-        });
-        CSVDestination { csv_writer, truncate: csv_options.truncate, no_headers: csv_options.no_headers }
+        };
+        let encoding = TextEncoding::from_name(&csv_options.encoding).unwrap();
+        if csv_options.bom {
+            writer.write_all(encoding.bom()).unwrap();
+        }
+        let mut builder = csv::WriterBuilder::new();
+        builder
+            .delimiter(csv_options.delimiter.unwrap_or(b','))
+            .quote_style(match csv_options.quote_style.as_str() {
+                "always" => csv::QuoteStyle::Always,
+                "never" => csv::QuoteStyle::Never,
+                "non-numeric" => csv::QuoteStyle::NonNumeric,
+                _ => csv::QuoteStyle::Necessary,
+            })
+            .terminator(match csv_options.line_terminator.as_str() {
+                "crlf" => csv::Terminator::CRLF,
+                _ => csv::Terminator::Any(b'\n'),
+            });
+        if let Some(escape) = csv_options.escape {
+            builder.double_quote(false).escape(escape);
+        }
+        let csv_writer = builder.from_writer(EncodingWriter::new(writer, encoding, csv_options.encoding_errors == "replace"));
+        CSVDestination {
+            csv_writer,
+            formatter: ValueFormatter::init(&csv_options.formatting, &csv_options.truncate),
+            no_headers: csv_options.no_headers,
+        }
+    }
+
+    //write errors include characters that can't be encoded, which user has to deal with
+    fn exit_on_error<T, E: std::fmt::Display>(result: Result<T, E>) -> T {
+        result.unwrap_or_else(|e| {
+            eprintln!("Error: can't write csv: {}", e);
+            std::process::exit(1);
+        })
     }
 
-    pub fn row_to_csv_row(row: &Row, truncate: Option<u64>) -> Vec<String> {
-        row.iter().map(|v| {
-            match v {
-                Value::U64(value) => value.to_string(),
-                Value::I64(value) => value.to_string(),
-                Value::U32(value) => value.to_string(),
-                Value::I32(value) => value.to_string(),
-                Value::U16(value) => value.to_string(),
-                Value::I16(value) => value.to_string(),
-                Value::U8(value) => value.to_string(),
-                Value::I8(value) => value.to_string(),
-                Value::F64(value) => value.to_string(),
-                Value::F32(value) => value.to_string(),
-                Value::String(value) => truncate_text_with_note(value.to_string(), truncate),
-                Value::Bool(value) => value.to_string(),
-                Value::Bytes(value) => escape_binary_data(&value),
-                Value::None => "".to_string(),
-                Value::Timestamp(value) => value.to_string(),
-                Value::Date(date) => format!("{}", date.format("%Y-%m-%d")),
-                Value::Time(time) => format!("{}", time.format("%H:%M:%S")),
-                Value::DateTime(datetime) => format!("{}", datetime.format("%Y-%m-%d %H:%M:%S")),
-                _ => panic!(format!("csv: unsupported type: {:?}", v))
-            }
-        }).collect()
+    pub fn row_to_csv_row(row: &Row, formatter: &ValueFormatter) -> Vec<String> {
+        row.iter().enumerate().map(|(idx, v)| formatter.format_column(idx, v)).collect()
     }
 }
 
@@ -54,25 +68,24 @@ impl DataDestination for CSVDestination
     fn prepare(&mut self) {}
 
     fn prepare_for_results(&mut self, result_iterator: &dyn DataSourceBatchIterator) {
+        let headers: Vec<String> = result_iterator
+            .get_column_info()
+            .iter()
+            .map(|c| c.name.clone())
+            .collect();
+        self.formatter.set_columns(&headers);
         if !self.no_headers {
-            let headers: Vec<String> = result_iterator
-                .get_column_info()
-                .iter()
-                .map(|c| c.name.clone())
-                .collect();
-            self.csv_writer.write_record(headers).unwrap();
+            CSVDestination::exit_on_error(self.csv_writer.write_record(headers));
         }
     }
 
     fn add_rows(&mut self, rows: &[Row]) {
         for row in rows {
-            self.csv_writer
-                .write_record(CSVDestination::row_to_csv_row(&row, self.truncate))
-                .unwrap();
+            CSVDestination::exit_on_error(self.csv_writer.write_record(CSVDestination::row_to_csv_row(&row, &self.formatter)));
         }
     }
 
     fn close(&mut self) {
-        self.csv_writer.flush().unwrap();
+        CSVDestination::exit_on_error(self.csv_writer.flush());
     }
 }
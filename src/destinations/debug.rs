@@ -6,11 +6,12 @@ use termcolor;
 
 
 use crate::commands::{ApplicationArguments, export::DebugDestinationOptions, UseColor};
-use crate::definitions::{Row, DataSourceBatchIterator, DataDestination};
+use crate::definitions::{Row, Value, DataSourceBatchIterator, DataDestination};
 use crate::utils::fileorstdout::FileOrStdout;
+use crate::utils::truncation::Truncation;
 
 pub struct DebugDestination {
-    truncate: Option<u64>,
+    truncation: Truncation,
     column_names: Vec<String>,
     writer: FileOrStdout,
     use_color: bool,
@@ -20,13 +21,13 @@ impl DebugDestination {
 
     pub fn init(args: &ApplicationArguments, options: &DebugDestinationOptions) -> DebugDestination {
         let use_color = match args.color {
-            UseColor::Yes => true,
-            UseColor::No => false,
-            UseColor::Auto => options.filename == "-" && atty::is(atty::Stream::Stdout),
+            Some(UseColor::Yes) => true,
+            Some(UseColor::No) => false,
+            None | Some(UseColor::Auto) => options.filename == "-" && atty::is(atty::Stream::Stdout),
         };
 
         DebugDestination {
-            truncate: options.truncate,
+            truncation: Truncation::init(&options.truncate),
             column_names: vec![],
             use_color,
             writer: match options.filename.as_ref() {
@@ -48,6 +49,7 @@ impl DataDestination for DebugDestination {
             .iter()
             .map(|col| { col.name.clone() })
             .collect();
+        self.truncation.set_columns(&self.column_names);
         self.writer.write_all("#columns\n".as_bytes()).unwrap();
         result_iterator
             .get_column_info()
@@ -59,8 +61,13 @@ impl DataDestination for DebugDestination {
     fn add_rows(&mut self, rows: &[Row]) {
 
         for row in rows {
-            for col in row.iter() {
-                self.writer.write_all(format!("{:?}", col).as_bytes()).unwrap();
+            for (idx, col) in row.iter().enumerate() {
+                let content = match col {
+                    Value::String(value) => format!("{:?}", Value::String(self.truncation.truncate_string(idx, value))),
+                    Value::Bytes(value) => format!("{:?}", Value::Bytes(self.truncation.truncate_bytes(idx, value).to_vec())),
+                    _ => format!("{:?}", col),
+                };
+                self.writer.write_all(content.as_bytes()).unwrap();
             }
             self.writer.write(&['\n' as u8]);
         }
@@ -0,0 +1,124 @@
+use std::path::Path;
+
+use duckdb::{self, appender_params_from_iter, types::TimeUnit};
+
+use crate::commands::export::DuckdbDestinationOptions;
+use crate::definitions::{ColumnType, Value, Row, DataDestination, DataSourceBatchIterator};
+use crate::utils::{quote_identifier, truncation::Truncation};
+
+
+pub struct DuckdbDestination {
+    connection: duckdb::Connection,
+    table: String,
+    truncation: Truncation,
+}
+
+impl DuckdbDestination {
+
+    pub fn init(duckdb_options: &DuckdbDestinationOptions) -> DuckdbDestination {
+        let path = Path::new(&duckdb_options.filename);
+        if path.exists() {
+            std::fs::remove_file(path).unwrap();
+        }
+        let connection = match duckdb::Connection::open(&duckdb_options.filename) {
+            Ok(connection) => connection,
+            Err(e) => {
+                eprintln!("Error: could not create {}: {}", duckdb_options.filename, e);
+                std::process::exit(1);
+            }
+        };
+        DuckdbDestination {
+            connection,
+            table: duckdb_options.table.clone(),
+            truncation: Truncation::init(&duckdb_options.truncate),
+        }
+    }
+}
+
+impl DataDestination for DuckdbDestination
+{
+
+    fn prepare(&mut self) {}
+
+    fn prepare_for_results(&mut self, result_iterator: &dyn DataSourceBatchIterator) {
+        let columns = result_iterator
+            .get_column_info()
+            .iter()
+            .map(|col| { format!("{} {}", quote_identifier(&col.name), match col.data_type {
+                ColumnType::U64 => "ubigint",
+                ColumnType::I64 => "bigint",
+                ColumnType::U32 => "uinteger",
+                ColumnType::I32 => "integer",
+                ColumnType::U16 => "usmallint",
+                ColumnType::I16 => "smallint",
+                ColumnType::U8 => "utinyint",
+                ColumnType::I8 => "tinyint",
+                ColumnType::F64 => "double",
+                ColumnType::F32 => "real",
+                ColumnType::String | ColumnType::JSON | ColumnType::None => "varchar",
+                ColumnType::Bytes => "blob",
+                ColumnType::Bool => "boolean",
+                ColumnType::DateTime => "timestamp",
+                ColumnType::Date => "date",
+                ColumnType::Time => "time",
+                //sources don't report precision and scale, widest decimal is used
+                ColumnType::Decimal => "decimal(38, 10)",
+                ColumnType::Timestamp => "ubigint",
+            })})
+            .collect::<Vec<String>>()
+            .join(", ");
+        let column_names: Vec<String> = result_iterator
+            .get_column_info()
+            .iter()
+            .map(|col| { col.name.clone() })
+            .collect();
+        self.truncation.set_columns(&column_names);
+
+        let create_table_query = format!("create table {} ({})", quote_identifier(&self.table), columns);
+        if let Err(e) = self.connection.execute_batch(&create_table_query) {
+            eprintln!("Error: could not create table {}: {}", self.table, e);
+            std::process::exit(1);
+        }
+    }
+
+    fn add_rows(&mut self, rows: &[Row]) {
+        let mut appender = match self.connection.appender(&self.table) {
+            Ok(appender) => appender,
+            Err(e) => {
+                eprintln!("Error: could not insert into {}: {}", self.table, e);
+                std::process::exit(1);
+            }
+        };
+        for row in rows {
+            let values = row.iter().enumerate().map(|(idx, col)| match col {
+                Value::U64(value) => duckdb::types::Value::UBigInt(*value),
+                Value::I64(value) => duckdb::types::Value::BigInt(*value),
+                Value::U32(value) => duckdb::types::Value::UInt(*value),
+                Value::I32(value) => duckdb::types::Value::Int(*value),
+                Value::U16(value) => duckdb::types::Value::USmallInt(*value),
+                Value::I16(value) => duckdb::types::Value::SmallInt(*value),
+                Value::U8(value) => duckdb::types::Value::UTinyInt(*value),
+                Value::I8(value) => duckdb::types::Value::TinyInt(*value),
+                Value::Bool(value) => duckdb::types::Value::Boolean(*value),
+                Value::String(value) => duckdb::types::Value::Text(self.truncation.truncate_string(idx, value)),
+                Value::F64(value) => duckdb::types::Value::Double(*value),
+                Value::F32(value) => duckdb::types::Value::Float(*value),
+                Value::Bytes(value) => duckdb::types::Value::Blob(self.truncation.truncate_bytes(idx, value).to_vec()),
+                Value::JSON(value) => duckdb::types::Value::Text(self.truncation.truncate_string(idx, value)),
+                Value::None => duckdb::types::Value::Null,
+                Value::Timestamp(value) => duckdb::types::Value::UBigInt(*value),
+                //appender converts text to column type
+                Value::Date(date) => duckdb::types::Value::Text(date.format("%Y-%m-%d").to_string()),
+                Value::Time(time) => duckdb::types::Value::Text(time.format("%H:%M:%S%.f").to_string()),
+                Value::DateTime(datetime) => duckdb::types::Value::Timestamp(TimeUnit::Microsecond, datetime.timestamp_micros()),
+            });
+            if let Err(e) = appender.append_row(appender_params_from_iter(values)) {
+                eprintln!("Error: could not insert into {}: {}", self.table, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    fn close(&mut self) { }
+
+}
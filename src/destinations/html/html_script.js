@@ -0,0 +1,133 @@
+function dbfishTable(section) {
+    var table = section.querySelector(".dbfish-table");
+    var headerRow = table.tHead.rows[0];
+    var tbody = table.tBodies[0];
+    var rows = Array.prototype.slice.call(tbody.rows);
+    var pageSize = parseInt(table.getAttribute("data-page-size"), 10) || 0;
+    var search = section.querySelector(".dbfish-search");
+    var summary = section.querySelector(".dbfish-summary");
+    var pagination = section.querySelector(".dbfish-pagination");
+    var columnFilters = [];
+    var visibleRows = rows;
+    var currentPage = 0;
+    var sortColumn = -1;
+    var sortAscending = true;
+
+    function cellText(row, idx) {
+        var cell = row.cells[idx];
+        return cell.classList.contains("null") ? "" : cell.textContent;
+    }
+
+    function isNumeric(idx) {
+        return headerRow.cells[idx].classList.contains("num");
+    }
+
+    function compare(a, b, idx) {
+        var aNull = a.cells[idx].classList.contains("null");
+        var bNull = b.cells[idx].classList.contains("null");
+        if (aNull || bNull) {
+            return aNull === bNull ? 0 : (aNull ? -1 : 1);
+        }
+        var x = cellText(a, idx);
+        var y = cellText(b, idx);
+        if (isNumeric(idx)) {
+            return parseFloat(x) - parseFloat(y);
+        }
+        return x.localeCompare(y);
+    }
+
+    function applyFilters() {
+        var term = search.value.toLowerCase();
+        visibleRows = rows.filter(function (row) {
+            for (var idx = 0; idx < columnFilters.length; idx++) {
+                var value = columnFilters[idx].value.toLowerCase();
+                if (value && cellText(row, idx).toLowerCase().indexOf(value) === -1) {
+                    return false;
+                }
+            }
+            if (!term) {
+                return true;
+            }
+            for (var i = 0; i < row.cells.length; i++) {
+                if (cellText(row, i).toLowerCase().indexOf(term) !== -1) {
+                    return true;
+                }
+            }
+            return false;
+        });
+        currentPage = 0;
+        render();
+    }
+
+    function sortBy(idx) {
+        sortAscending = sortColumn === idx ? !sortAscending : true;
+        sortColumn = idx;
+        for (var i = 0; i < headerRow.cells.length; i++) {
+            headerRow.cells[i].classList.remove("sort-asc", "sort-desc");
+        }
+        headerRow.cells[idx].classList.add(sortAscending ? "sort-asc" : "sort-desc");
+        var sign = sortAscending ? 1 : -1;
+        rows.sort(function (a, b) { return sign * compare(a, b, idx); });
+        rows.forEach(function (row) { tbody.appendChild(row); });
+        applyFilters();
+    }
+
+    function pageCount() {
+        return pageSize > 0 ? Math.max(1, Math.ceil(visibleRows.length / pageSize)) : 1;
+    }
+
+    function addPageButton(label, page, disabled, active) {
+        var button = document.createElement("button");
+        button.textContent = label;
+        button.disabled = disabled;
+        if (active) {
+            button.className = "active";
+        }
+        button.addEventListener("click", function () {
+            currentPage = page;
+            render();
+        });
+        pagination.appendChild(button);
+    }
+
+    function render() {
+        var start = pageSize > 0 ? currentPage * pageSize : 0;
+        var end = pageSize > 0 ? start + pageSize : visibleRows.length;
+        rows.forEach(function (row) { row.style.display = "none"; });
+        visibleRows.slice(start, end).forEach(function (row) { row.style.display = ""; });
+        summary.textContent = visibleRows.length === rows.length
+            ? rows.length + " rows"
+            : visibleRows.length + " of " + rows.length + " rows";
+
+        pagination.innerHTML = "";
+        var pages = pageCount();
+        if (pages <= 1) {
+            return;
+        }
+        addPageButton("«", currentPage - 1, currentPage === 0, false);
+        for (var page = 0; page < pages; page++) {
+            if (page === 0 || page === pages - 1 || Math.abs(page - currentPage) <= 2) {
+                addPageButton(String(page + 1), page, false, page === currentPage);
+            } else if (Math.abs(page - currentPage) === 3) {
+                pagination.appendChild(document.createTextNode(" … "));
+            }
+        }
+        addPageButton("»", currentPage + 1, currentPage === pages - 1, false);
+    }
+
+    var filterRow = table.tHead.insertRow(-1);
+    filterRow.className = "filters";
+    Array.prototype.forEach.call(headerRow.cells, function (th, idx) {
+        th.addEventListener("click", function () { sortBy(idx); });
+        var input = document.createElement("input");
+        input.type = "search";
+        input.placeholder = "filter";
+        input.addEventListener("input", applyFilters);
+        filterRow.appendChild(document.createElement("th")).appendChild(input);
+        columnFilters.push(input);
+    });
+    search.addEventListener("input", applyFilters);
+    render();
+}
+
+Array.prototype.forEach.call(document.querySelectorAll(".dbfish-section"), dbfishTable);
@@ -1,100 +1,272 @@
 use std;
-use std::io::Write;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
 
 use askama_escape::{self, escape};
+use humantime;
 
 use crate::commands::export::HTMLDestinationOptions;
-use crate::definitions::{Value, Row, DataSourceBatchIterator, DataDestination};
+use crate::definitions::{ColumnType, Value, Row, DataSourceBatchIterator, DataDestination, ResultsInfo};
 use crate::utils::fileorstdout::FileOrStdout;
-use crate::utils::{escape_binary_data, truncate_text_with_note};
+use crate::utils::formatter::ValueFormatter;
 
-pub struct HTMLDestination {
-    truncate: Option<u64>,
+static SQL_KEYWORDS: [&str; 54] = [
+    "ALL", "AND", "AS", "ASC", "BETWEEN", "BY", "CASE", "CAST", "CREATE", "CROSS", "DELETE",
+    "DESC", "DISTINCT", "DROP", "ELSE", "END", "EXISTS", "FALSE", "FROM", "FULL", "GROUP",
+    "HAVING", "IN", "INNER", "INSERT", "INTERVAL", "INTO", "IS", "JOIN", "LEFT", "LIKE", "LIMIT",
+    "NOT", "NULL", "OFFSET", "ON", "OR", "ORDER", "OUTER", "OVER", "PARTITION", "RIGHT", "SELECT",
+    "SET", "TABLE", "THEN", "TRUE", "UNION", "UPDATE", "USING", "VALUES", "WHEN", "WHERE", "WITH",
+];
+
+///results of a single query. Rows are streamed to temporary file until the whole report can be written,
+///since table of contents and row counts come before them
+struct HTMLSection {
     column_names: Vec<String>,
+    numeric_columns: Vec<bool>,
+    rows: BufWriter<File>,
+    results_info: Option<ResultsInfo>,
+}
+
+pub struct HTMLDestination {
+    formatter: ValueFormatter,
+    sections: Vec<HTMLSection>,
     writer: FileOrStdout,
     title: String,
+    page_size: u64,
+}
+
+
+///numeric columns are right-aligned and sorted by value rather than as text
+pub fn is_numeric_column(column_type: &ColumnType) -> bool {
+    match column_type {
+        ColumnType::U64 | ColumnType::I64
+        | ColumnType::U32 | ColumnType::I32
+        | ColumnType::U16 | ColumnType::I16
+        | ColumnType::U8 | ColumnType::I8
+        | ColumnType::F64 | ColumnType::F32
+        | ColumnType::Decimal | ColumnType::Timestamp => true,
+        _ => false,
+    }
+}
+
+fn html_span(class: &str, content: &str) -> String {
+    format!("<span class=\"{}\">{}</span>", class, escape(content, askama_escape::Html))
+}
+
+///escape sql for html, wrapping keywords, strings, numbers and comments in spans
+pub fn highlight_sql(sql: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut result = String::with_capacity(sql.len() * 2);
+    let mut idx = 0;
+    while idx < chars.len() {
+        let start = idx;
+        let ch = chars[idx];
+        if ch == '-' && chars.get(idx + 1) == Some(&'-') {
+            while idx < chars.len() && chars[idx] != '\n' { idx += 1; }
+            result.push_str(&html_span("com", &chars[start..idx].iter().collect::<String>()));
+        } else if ch == '/' && chars.get(idx + 1) == Some(&'*') {
+            idx += 2;
+            while idx < chars.len() && !(chars[idx - 1] == '*' && chars[idx] == '/') { idx += 1; }
+            idx = (idx + 1).min(chars.len());
+            result.push_str(&html_span("com", &chars[start..idx].iter().collect::<String>()));
+        } else if ch == '\'' {
+            idx += 1;
+            while idx < chars.len() {
+                if chars[idx] == '\'' {
+                    if chars.get(idx + 1) == Some(&'\'') {
+                        idx += 1;
+                    } else {
+                        break;
+                    }
+                }
+                idx += 1;
+            }
+            idx = (idx + 1).min(chars.len());
+            result.push_str(&html_span("str", &chars[start..idx].iter().collect::<String>()));
+        } else if ch.is_ascii_digit() {
+            while idx < chars.len() && (chars[idx].is_ascii_alphanumeric() || chars[idx] == '.') { idx += 1; }
+            result.push_str(&html_span("num", &chars[start..idx].iter().collect::<String>()));
+        } else if ch.is_alphabetic() || ch == '_' {
+            while idx < chars.len() && (chars[idx].is_alphanumeric() || chars[idx] == '_') { idx += 1; }
+            let word: String = chars[start..idx].iter().collect();
+            if SQL_KEYWORDS.contains(&word.to_uppercase().as_str()) {
+                result.push_str(&html_span("kw", &word));
+            } else {
+                result.push_str(&escape(&word, askama_escape::Html).to_string());
+            }
+        } else {
+            idx += 1;
+            result.push_str(&escape(&ch.to_string(), askama_escape::Html).to_string());
+        }
+    }
+    result
 }
 
 
 impl HTMLDestination {
 
     pub fn init(options: &HTMLDestinationOptions) -> HTMLDestination {
-        
+
         HTMLDestination {
-            truncate: options.truncate,
-            column_names: vec![],
+            formatter: ValueFormatter::init(&options.formatting, &options.truncate),
+            sections: vec![],
             writer: match options.filename.as_ref() {
                 "-" =>  FileOrStdout::ColorStdout(termcolor::StandardStream::stdout(termcolor::ColorChoice::Auto)),
                 _ => FileOrStdout::File(std::fs::File::create(options.filename.clone()).unwrap())
             },
             title: options.title.clone().unwrap_or_else(|| "".to_string()),
+            page_size: options.page_size,
         }
     }
+
+    fn write_section(&mut self, idx: usize, section_count: usize, section: HTMLSection) {
+        let heading = if section_count > 1 {
+            format!("<h2>Query {}</h2>\n", idx + 1)
+        } else {
+            "".to_string()
+        };
+        let (source, exported_at, row_count, duration, sql) = match &section.results_info {
+            Some(info) => (
+                escape(&info.source_name, askama_escape::Html).to_string(),
+                info.started.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                info.row_count.to_string(),
+                humantime::format_duration(std::time::Duration::from_millis(info.duration.as_millis() as u64)).to_string(),
+                highlight_sql(info.query.trim()),
+            ),
+            None => ("".to_string(), "".to_string(), "".to_string(), "".to_string(), "".to_string()),
+        };
+        let mut content = format!(
+            include_str!("html_section_prefix.html"),
+            id=format!("result-{}", idx + 1),
+            heading=heading,
+            source=source,
+            exported_at=exported_at,
+            row_count=row_count,
+            duration=duration,
+            sql=sql,
+            page_size=self.page_size,
+        );
+        content.push_str("<thead><tr class=\"header\">\n");
+        for (name, numeric) in section.column_names.iter().zip(section.numeric_columns.iter()) {
+            content.push_str(if *numeric { "    <th class=\"num\">" } else { "    <th>" });
+            content.push_str(escape(&name, askama_escape::Html).to_string().as_ref());
+            content.push_str("</th>\n");
+        };
+        content.push_str("</tr></thead><tbody>\n");
+        self.writer.write_all(content.as_bytes()).unwrap();
+        let mut rows = section.rows.into_inner().unwrap();
+        rows.seek(SeekFrom::Start(0)).unwrap();
+        std::io::copy(&mut rows, &mut self.writer).unwrap();
+        self.writer.write_all(include_bytes!("html_section_suffix.html")).unwrap();
+    }
 }
 
 impl DataDestination for HTMLDestination {
-    
+
     fn prepare(&mut self) {}
 
     fn prepare_for_results(&mut self, result_iterator: &dyn DataSourceBatchIterator) {
-        self.writer
-            .write_all(format!(include_str!("html_prefix.html"), title=escape(&self.title, askama_escape::Html)).as_bytes())
-            .unwrap();
-
-        self.column_names = result_iterator
-            .get_column_info()
+        let column_info = result_iterator.get_column_info();
+        let column_names: Vec<String> = column_info
             .iter()
             .map(|col| { col.name.clone() })
             .collect();
-        self.writer.write_all(b"<thead><tr>\n").unwrap();
-        for name in self.column_names.iter() {
-            self.writer
-                .write_all(
-                    ("    <th>".to_string() + escape(&name, askama_escape::Html).to_string().as_ref() + "</th>\n")
-                    .as_bytes())
-                .unwrap();
-        };
-        self.writer.write_all(b"</tr></thead><tbody>\n").unwrap();
+        self.formatter.set_columns(&column_names);
+        self.sections.push(HTMLSection {
+            column_names,
+            numeric_columns: column_info
+                .iter()
+                .map(|col| { is_numeric_column(&col.data_type) })
+                .collect(),
+            rows: BufWriter::new(tempfile::tempfile().unwrap()),
+            results_info: None,
+        });
     }
-    fn add_rows(&mut self, rows: &[Row]) {
 
+    fn add_rows(&mut self, rows: &[Row]) {
+        let section = self.sections.last_mut().unwrap();
         for row in rows {
-            //<column index, value, original length, truncated>
-            let mut row_data: Vec<String> = Vec::with_capacity(self.column_names.len());
-            for col in row.iter() {
-                let content = escape(& match col {
-                    Value::U64(value) => value.to_string(),
-                    Value::I64(value) => value.to_string(),
-                    Value::U32(value) => value.to_string(),
-                    Value::I32(value) => value.to_string(),
-                    Value::U16(value) => value.to_string(),
-                    Value::I16(value) => value.to_string(),
-                    Value::U8(value) => value.to_string(),
-                    Value::I8(value) => value.to_string(),
-                    Value::F64(value) => value.to_string(),
-                    Value::F32(value) => value.to_string(),
-                    Value::String(value) => truncate_text_with_note(value.to_string(), self.truncate),
-                    Value::Bool(value) => value.to_string(),
-                    Value::Bytes(value) => escape_binary_data(&value),
-                    Value::None => "".to_string(),
-                    Value::Timestamp(value) => value.to_string(),
-                    Value::Date(date) => format!("{}", date.format("%Y-%m-%d")),
-                    Value::Time(time) => format!("{}", time.format("%H:%M:%S")),
-                    Value::DateTime(datetime) => format!("{}", datetime.format("%Y-%m-%d %H:%M:%S")),
-                   
-                    _ => panic!(format!("text: unsupported type: {:?}", col))
-                }, askama_escape::Html).to_string();
-                row_data.push(content);
+            //<css class, value>
+            let mut row_data: Vec<(&str, String)> = Vec::with_capacity(section.column_names.len());
+            for (idx, col) in row.iter().enumerate() {
+                let content = escape(&self.formatter.format_column(idx, col), askama_escape::Html).to_string();
+                let class = match col {
+                    Value::None => "null",
+                    _ if section.numeric_columns.get(idx) == Some(&true) => "num",
+                    _ => "",
+                };
+                row_data.push((class, content));
+            }
+            let mut html = "<tr>\n".to_string();
+            for (class, v) in row_data {
+                match class {
+                    "" => html.push_str(&format!("    <td>{}</td>\n", v)),
+                    _ => html.push_str(&format!("    <td class=\"{}\">{}</td>\n", class, v)),
+                }
             }
-            let row_str = "<tr>\n".to_string() + row_data.iter().map(|v| "    <td>".to_string() + v + "</td>\n").collect::<Vec<String>>().join("").as_ref() + "</tr>\n";
-            self.writer.write_all(row_str.as_bytes()).unwrap();
+            html.push_str("</tr>\n");
+            section.rows.write_all(html.as_bytes()).unwrap();
+        }
+    }
+
+    fn finish_results(&mut self, results_info: &ResultsInfo) {
+        if let Some(section) = self.sections.last_mut() {
+            section.results_info = Some(results_info.clone());
         }
     }
 
     fn close(&mut self) {
-        self.writer.write_all(include_bytes!("html_suffix.html")).unwrap();
+        self.writer
+            .write_all(
+                format!(
+                    include_str!("html_prefix.html"),
+                    title=escape(&self.title, askama_escape::Html),
+                    style=include_str!("html_style.css"),
+                ).as_bytes()
+            )
+            .unwrap();
+        if !self.title.is_empty() {
+            self.writer
+                .write_all(format!("<h1>{}</h1>\n", escape(&self.title, askama_escape::Html)).as_bytes())
+                .unwrap();
+        }
+        if self.sections.len() > 1 {
+            let mut toc = "<ul class=\"toc\">\n".to_string();
+            for (idx, section) in self.sections.iter().enumerate() {
+                toc.push_str(&format!(
+                    "    <li><a href=\"#result-{idx}\">Query {idx}</a> ({rows} rows)</li>\n",
+                    idx=idx + 1,
+                    rows=section.results_info.as_ref().map(|info| info.row_count).unwrap_or(0),
+                ));
+            }
+            toc.push_str("</ul>\n");
+            self.writer.write_all(toc.as_bytes()).unwrap();
+        }
+        let sections = std::mem::take(&mut self.sections);
+        let section_count = sections.len();
+        for (idx, section) in sections.into_iter().enumerate() {
+            self.write_section(idx, section_count, section);
+        }
+        self.writer
+            .write_all(format!(include_str!("html_suffix.html"), script=include_str!("html_script.js")).as_bytes())
+            .unwrap();
         self.writer.flush().unwrap();
     }
 
 }
 
+
+#[cfg(test)]
+mod tests {
+
+    use super::highlight_sql;
+
+    #[test]
+    fn test_highlight_sql() {
+        assert_eq!(
+            highlight_sql("select 'a<b' from t -- x"),
+            "<span class=\"kw\">select</span> <span class=\"str\">&#x27;a&lt;b&#x27;</span> <span class=\"kw\">from</span> t <span class=\"com\">-- x</span>"
+        );
+    }
+
+}
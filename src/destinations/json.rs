@@ -7,11 +7,11 @@ use json_color;
 use crate::commands::{ApplicationArguments, export::JSONDestinationOptions, UseColor};
 use crate::definitions::{Value, Row, DataSourceBatchIterator, DataDestination};
 use crate::utils::fileorstdout::FileOrStdout;
-use crate::utils::{escape_binary_data, truncate_text_with_note};
+use crate::utils::formatter::ValueFormatter;
 
 pub struct JSONDestination {
     writer: FileOrStdout,
-    truncate: Option<u64>,
+    formatter: ValueFormatter,
     indent: u16,
     compact: bool,
     first_row: bool,
@@ -24,9 +24,9 @@ impl JSONDestination
 {
     pub fn init(args: &ApplicationArguments, json_options: &JSONDestinationOptions) -> JSONDestination {
         let use_color = match args.color {
-            UseColor::Yes => true,
-            UseColor::No => false,
-            UseColor::Auto => json_options.filename == "-" && atty::is(atty::Stream::Stdout),
+            Some(UseColor::Yes) => true,
+            Some(UseColor::No) => false,
+            None | Some(UseColor::Auto) => json_options.filename == "-" && atty::is(atty::Stream::Stdout),
         };
         let writer = match json_options.filename.as_str() {
             "-" => FileOrStdout::ColorStdout(termcolor::StandardStream::stdout(if use_color { termcolor::ColorChoice::Always} else { termcolor::ColorChoice::Never })),
@@ -37,8 +37,8 @@ impl JSONDestination
             column_names: vec![],
             compact: json_options.compact,
             first_row: true,
-            indent: json_options.indent,
-            truncate: json_options.truncate,
+            indent: json_options.indent.unwrap_or(4),
+            formatter: ValueFormatter::init(&json_options.formatting, &json_options.truncate),
             writer,
             json_colorizer: json_color::Colorizer::arbitrary()
         }
@@ -56,17 +56,12 @@ impl JSONDestination
                     Value::I16(value) => json::JsonValue::Number(json::number::Number::from(*value)),
                     Value::U8(value) => json::JsonValue::Number(json::number::Number::from(*value)),
                     Value::I8(value) => json::JsonValue::Number(json::number::Number::from(*value)),
-                    Value::F64(value) => json::JsonValue::Number(json::number::Number::from(*value)),
-                    Value::F32(value) => json::JsonValue::Number(json::number::Number::from(*value)),
-                    Value::String(value) => json::JsonValue::String(truncate_text_with_note(value.to_string(), self.truncate)),
-                    Value::Bool(value) => json::JsonValue::Boolean(*value),
-                    Value::Bytes(value) =>  json::JsonValue::String(escape_binary_data(value)),
-                    Value::None => json::JsonValue::Null,
+                    Value::F64(value) => json::JsonValue::Number(json::number::Number::from(self.formatter.round_float(*value))),
+                    Value::F32(value) => json::JsonValue::Number(json::number::Number::from(self.formatter.round_float(f64::from(*value)))),
+                    Value::Bool(value) if self.formatter.bool_format.is_none() => json::JsonValue::Boolean(*value),
+                    Value::None if self.formatter.null_as.is_none() => json::JsonValue::Null,
                     Value::Timestamp(value) => json::JsonValue::Number(json::number::Number::from(*value)),
-                    Value::Date(date) => json::JsonValue::String(format!("{}", date.format("%Y-%m-%d"))),
-                    Value::Time(time) => json::JsonValue::String(format!("{}", time.format("%H:%M:%S"))),
-                    Value::DateTime(datetime) => json::JsonValue::String(format!("{}", datetime.format("%Y-%m-%d %H:%M:%S"))),
-                    _ => panic!(format!("json: unsupported type: {:?}", v))
+                    _ => json::JsonValue::String(self.formatter.format_column(idx, v)),
                 };
                 json_row.insert(&self.column_names[idx], value);
             });
@@ -85,6 +80,7 @@ impl DataDestination for JSONDestination
             .iter()
             .map(|c| c.name.clone())
             .collect();
+        self.formatter.set_columns(&self.column_names);
         self.writer.write_all(if self.compact { b"[" } else { b"[\n" }).unwrap();
 
     }
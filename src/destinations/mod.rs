@@ -1,8 +1,10 @@
-use crate::definitions::{DataDestination, DataSourceBatchIterator, Row};
+use crate::definitions::{DataDestination, DataSourceBatchIterator, ResultsInfo, Row};
 
 #[cfg(feature = "use_csv")]
 pub mod csv;
 pub mod debug;
+#[cfg(feature = "use_duckdb")]
+pub mod duckdb;
 #[cfg(feature = "use_html")]
 pub mod html;
 #[cfg(feature = "use_json")]
@@ -27,6 +29,8 @@ pub enum Destination {
     JSON(json::JSONDestination),
     #[cfg(feature = "use_sqlite")]
     Sqlite(sqlite::SqliteDestination),
+    #[cfg(feature = "use_duckdb")]
+    Duckdb(duckdb::DuckdbDestination),
     #[cfg(feature = "use_spsheet")]
     SpreadSheet(ods_xlsx::SpreadSheetDestination),
     #[cfg(feature = "use_text")]
@@ -35,6 +39,18 @@ pub enum Destination {
     TextVertical(text_vertical::TextVerticalDestination),
 }
 
+impl Destination {
+
+    ///whether destination can store results of more than one query
+    pub fn supports_multiple_results(&self) -> bool {
+        match self {
+            #[cfg(feature = "use_html")]
+            Destination::HTML(_) => true,
+            _ => false,
+        }
+    }
+}
+
 impl DataDestination for Destination {
 
     fn prepare(&mut self) {
@@ -50,6 +66,8 @@ impl DataDestination for Destination {
             Destination::SpreadSheet(spreadsheet_destination) => spreadsheet_destination.prepare(),
             #[cfg(feature = "use_sqlite")]
             Destination::Sqlite(sqlite_destination) => sqlite_destination.prepare(),
+            #[cfg(feature = "use_duckdb")]
+            Destination::Duckdb(duckdb_destination) => duckdb_destination.prepare(),
             #[cfg(feature = "use_text")]
             Destination::Text(text_destination) => text_destination.prepare(),
             #[cfg(feature = "use_text")]
@@ -72,6 +90,8 @@ impl DataDestination for Destination {
             Destination::SpreadSheet(spreadsheet_destination) => spreadsheet_destination.prepare_for_results(result_iterator),
             #[cfg(feature = "use_sqlite")]
             Destination::Sqlite(sqlite_destination) => sqlite_destination.prepare_for_results(result_iterator),
+            #[cfg(feature = "use_duckdb")]
+            Destination::Duckdb(duckdb_destination) => duckdb_destination.prepare_for_results(result_iterator),
             #[cfg(feature = "use_text")]
             Destination::Text(text_destination) => text_destination.prepare_for_results(result_iterator),
             #[cfg(feature = "use_text")]
@@ -92,6 +112,8 @@ impl DataDestination for Destination {
             Destination::SpreadSheet(spreadsheet_destination) => spreadsheet_destination.add_rows(rows),
             #[cfg(feature = "use_sqlite")]
             Destination::Sqlite(sqlite_destination) => sqlite_destination.add_rows(rows),
+            #[cfg(feature = "use_duckdb")]
+            Destination::Duckdb(duckdb_destination) => duckdb_destination.add_rows(rows),
             #[cfg(feature = "use_text")]
             Destination::Text(text_destination) => text_destination.add_rows(rows),
             #[cfg(feature = "use_text")]
@@ -99,6 +121,14 @@ impl DataDestination for Destination {
         }
     }
 
+    fn finish_results(&mut self, results_info: &ResultsInfo) {
+        match self {
+            #[cfg(feature = "use_html")]
+            Destination::HTML(html_destination) => html_destination.finish_results(results_info),
+            _ => {},
+        }
+    }
+
     fn close(&mut self) {
         match self {
             #[cfg(feature = "use_csv")]
@@ -112,6 +142,8 @@ impl DataDestination for Destination {
             Destination::SpreadSheet(spreadsheet_destination) => spreadsheet_destination.close(),
             #[cfg(feature = "use_sqlite")]
             Destination::Sqlite(sqlite_destination) => sqlite_destination.close(),
+            #[cfg(feature = "use_duckdb")]
+            Destination::Duckdb(duckdb_destination) => duckdb_destination.close(),
             #[cfg(feature = "use_text")]
             Destination::Text(text_destination) => text_destination.close(),
             #[cfg(feature = "use_text")]
@@ -5,7 +5,7 @@ use std::path::Path;
 
 use crate::commands::export::SpreadSheetDestinationOptions;
 use crate::definitions::{Value, Row, DataSourceBatchIterator, DataDestination};
-use crate::utils::{escape_binary_data, truncate_text_with_note};
+use crate::utils::formatter::ValueFormatter;
 
 
 pub enum SpreadSheetFormat {
@@ -18,31 +18,20 @@ pub struct SpreadSheetDestination {
     sheet: Sheet,
     sheet_row_count: usize,
     format: SpreadSheetFormat,
-    truncate: Option<u64>,
+    formatter: ValueFormatter,
 }
 
 
-pub fn value_to_cell(value: &Value, truncate: Option<u64>) -> Cell {
+///numbers and dates are stored as native cells unless custom format was requested
+pub fn value_to_cell(idx: usize, value: &Value, formatter: &ValueFormatter) -> Cell {
     match value {
-        Value::U64(value) => Cell::str(value.to_string()),
-        Value::I64(value) => Cell::str(value.to_string()),
-        Value::U32(value) => Cell::str(value.to_string()),
-        Value::I32(value) => Cell::str(value.to_string()),
-        Value::U16(value) => Cell::str(value.to_string()),
-        Value::I16(value) => Cell::str(value.to_string()),
-        Value::U8(value) => Cell::str(value.to_string()),
-        Value::I8(value) => Cell::str(value.to_string()),
-        Value::F64(value) => Cell::float(*value),
-        Value::F32(value) => Cell::float(f64::from(*value)),
-        Value::String(value) => Cell::str(truncate_text_with_note(value.to_string(), truncate)),
-        Value::Bool(value) => Cell::str(value.to_string()),
-        Value::Bytes(value) => Cell::str(escape_binary_data(&value)),
-        Value::None => Cell::str("".to_string()),
-        Value::Timestamp(value) => Cell::str(value.to_string()),
-        Value::Date(date) => Cell::date_with_style(format!("{}", date.format("%Y-%m-%d")), Style::new("YYYY/MM/DD")),
-        Value::Time(time) => Cell::str(format!("{}", time.format("%H:%M:%S"))),
-        Value::DateTime(datetime) => Cell::date_with_style(format!("{}", datetime.format("%Y-%m-%dT%H:%M:%S")), Style::new("YYYY/MM/DD\\ HH:MM:SS")),
-        _ => panic!(format!("spsheet: unsupported type: {:?}", value)),
+        Value::F64(value) if formatter.float_precision.is_none() => Cell::float(*value),
+        Value::F32(value) if formatter.float_precision.is_none() => Cell::float(f64::from(*value)),
+        Value::Date(date) if formatter.date_format.is_none() =>
+            Cell::date_with_style(format!("{}", date.format("%Y-%m-%d")), Style::new("YYYY/MM/DD")),
+        Value::DateTime(datetime) if formatter.datetime_format.is_none() =>
+            Cell::date_with_style(format!("{}", datetime.format("%Y-%m-%dT%H:%M:%S")), Style::new("YYYY/MM/DD\\ HH:MM:SS")),
+        _ => Cell::str(formatter.format_column(idx, value)),
     }
 }
 
@@ -54,7 +43,7 @@ impl SpreadSheetDestination
             sheet: Sheet::new("sheet 1"),
             sheet_row_count: 0,
             format,
-            truncate: spreadsheet_options.truncate,
+            formatter: ValueFormatter::init(&spreadsheet_options.formatting, &spreadsheet_options.truncate),
         }
     }
 }
@@ -64,8 +53,14 @@ impl DataDestination for SpreadSheetDestination
     fn prepare(&mut self) {}
 
     fn prepare_for_results(&mut self, result_iterator: &dyn DataSourceBatchIterator) {
-        for (idx, column) in result_iterator.get_column_info().iter().enumerate() {
-            self.sheet.add_cell(Cell::str(column.name.clone()), 0, idx);
+        let column_names: Vec<String> = result_iterator
+            .get_column_info()
+            .iter()
+            .map(|column| column.name.clone())
+            .collect();
+        self.formatter.set_columns(&column_names);
+        for (idx, name) in column_names.into_iter().enumerate() {
+            self.sheet.add_cell(Cell::str(name), 0, idx);
         }
         self.sheet_row_count += 1;
     }
@@ -73,7 +68,7 @@ impl DataDestination for SpreadSheetDestination
     fn add_rows(&mut self, rows: &[Row]) {
         for row in rows {
             for (idx, val) in row.iter().enumerate() {
-                self.sheet.add_cell(value_to_cell(val, self.truncate), self.sheet_row_count, idx);
+                self.sheet.add_cell(value_to_cell(idx, val, &self.formatter), self.sheet_row_count, idx);
             }
             self.sheet_row_count += 1;
         }
@@ -4,14 +4,14 @@ use sqlite;
 
 use crate::commands::export::SqliteDestinationOptions;
 use crate::definitions::{ColumnType, Value, Row, DataDestination, DataSourceBatchIterator};
-use crate::utils::truncate_text_with_note;
+use crate::utils::{quote_identifier, truncation::Truncation};
 
 
 pub struct SqliteDestination {
     connection: sqlite::Connection,
     table: String,
     column_names: Vec<String>,
-    truncate: Option<u64>,
+    truncation: Truncation,
 }
 
 impl SqliteDestination {
@@ -25,7 +25,7 @@ impl SqliteDestination {
             connection: sqlite::Connection::open(&sqlite_options.filename).unwrap(),
             table: sqlite_options.table.clone(),
             column_names: vec![],
-            truncate: sqlite_options.truncate,
+            truncation: Truncation::init(&sqlite_options.truncate),
         }
     }
 }
@@ -39,7 +39,7 @@ impl DataDestination for SqliteDestination
         let columns = result_iterator
             .get_column_info()
             .iter()
-            .map(|col| { format!("{} {}", col.name, match col.data_type {
+            .map(|col| { format!("{} {}", quote_identifier(&col.name), match col.data_type {
                 ColumnType::U64 | ColumnType::I64
                 | ColumnType::U32 | ColumnType::I32
                 | ColumnType::U16 | ColumnType::I16
@@ -52,7 +52,9 @@ impl DataDestination for SqliteDestination
                 ColumnType::Date => "date".to_string(),
                 ColumnType::Time => "time".to_string(),
                 ColumnType::Decimal => "numeric".to_string(),
-                _ => panic!(format!("sqlite: unsupported column type: {:?}", col.data_type))
+                ColumnType::JSON => "text".to_string(),
+                ColumnType::Timestamp => "integer".to_string(),
+                ColumnType::None => "".to_string(),
             })})
             .collect::<Vec<String>>()
             .join(", ");
@@ -61,8 +63,9 @@ impl DataDestination for SqliteDestination
             .iter()
             .map(|col| { col.name.clone() })
             .collect();
+        self.truncation.set_columns(&self.column_names);
 
-        let create_table_query =format!("create table {} ({})", self.table, columns);
+        let create_table_query =format!("create table {} ({})", quote_identifier(&self.table), columns);
         self.connection.execute(create_table_query).unwrap();
     }
 
@@ -70,8 +73,8 @@ impl DataDestination for SqliteDestination
         let values_part = self.column_names.iter().map(|_| {"?".to_string()}).collect::<Vec<String>>().join(", ");
         let mut sql = format!(
             "insert into {} ({}) values ({})",
-            self.table,
-            self.column_names.join(", "),
+            quote_identifier(&self.table),
+            self.column_names.iter().map(|name| quote_identifier(name)).collect::<Vec<String>>().join(", "),
             values_part
         );
         for _v in 1..rows.len() {
@@ -81,7 +84,7 @@ impl DataDestination for SqliteDestination
         let mut cursor = statement.cursor();
         let mut data: Vec<sqlite::Value> = Vec::with_capacity(self.column_names.len());
         for row in rows {
-            for col in row.iter() {
+            for (idx, col) in row.iter().enumerate() {
                 match col {
                     Value::U64(value) => data.push(sqlite::Value::Integer(*value as i64)),
                     Value::I64(value) => data.push(sqlite::Value::Integer(*value)),
@@ -92,11 +95,17 @@ impl DataDestination for SqliteDestination
                     Value::U8(value) => data.push(sqlite::Value::Integer(i64::from(*value))),
                     Value::I8(value) => data.push(sqlite::Value::Integer(i64::from(*value))),
                     Value::Bool(value) => data.push(sqlite::Value::Integer(i64::from(*value))),
-                    Value::String(value) => data.push(sqlite::Value::String(truncate_text_with_note(value.to_string(), self.truncate))),
+                    Value::String(value) => data.push(sqlite::Value::String(self.truncation.truncate_string(idx, value))),
                     Value::F64(value) => data.push(sqlite::Value::Float(*value)),
                     Value::F32(value) => data.push(sqlite::Value::Float(f64::from(*value))),
-                    Value::Bytes(value) => data.push(sqlite::Value::Binary(value.clone())),
-                    _ => panic!(format!("sqlite: unsupported type: {:?}", col))
+                    Value::Bytes(value) => data.push(sqlite::Value::Binary(self.truncation.truncate_bytes(idx, value).to_vec())),
+                    Value::JSON(value) => data.push(sqlite::Value::String(self.truncation.truncate_string(idx, value))),
+                    Value::None => data.push(sqlite::Value::Null),
+                    Value::Timestamp(value) => data.push(sqlite::Value::Integer(*value as i64)),
+                    //text in formats understood by sqlite date and time functions
+                    Value::Date(date) => data.push(sqlite::Value::String(date.format("%Y-%m-%d").to_string())),
+                    Value::Time(time) => data.push(sqlite::Value::String(time.format("%H:%M:%S%.f").to_string())),
+                    Value::DateTime(datetime) => data.push(sqlite::Value::String(datetime.format("%Y-%m-%d %H:%M:%S%.f").to_string())),
                 }
             }
         }
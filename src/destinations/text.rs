@@ -7,12 +7,12 @@ use termcolor;
 
 
 use crate::commands::{ApplicationArguments, export::TextDestinationOptions, UseColor};
-use crate::definitions::{Value, Row, DataSourceBatchIterator, DataDestination};
+use crate::definitions::{Row, DataSourceBatchIterator, DataDestination};
 use crate::utils::fileorstdout::FileOrStdout;
-use crate::utils::{escape_binary_data, truncate_text_with_note};
+use crate::utils::formatter::ValueFormatter;
 
 pub struct TextDestination {
-    truncate: Option<u64>,
+    formatter: ValueFormatter,
     column_names: Vec<String>,  
     writer: FileOrStdout,
     table: Table,
@@ -23,16 +23,16 @@ impl TextDestination {
 
     pub fn init(args: &ApplicationArguments, options: &TextDestinationOptions) -> TextDestination {
         let use_color = match args.color {
-            UseColor::Yes => true,
-            UseColor::No => false,
-            UseColor::Auto => options.filename == "-" && atty::is(atty::Stream::Stdout),
+            Some(UseColor::Yes) => true,
+            Some(UseColor::No) => false,
+            None | Some(UseColor::Auto) => options.filename == "-" && atty::is(atty::Stream::Stdout),
         };
        
         let mut table = Table::new();
         table.set_format(*prettytable::format::consts::FORMAT_BOX_CHARS);
 
         TextDestination {
-            truncate: options.truncate,
+            formatter: ValueFormatter::init(&options.formatting, &options.truncate),
             column_names: vec![],
             use_color,
             writer: match options.filename.as_ref() {
@@ -55,6 +55,7 @@ impl DataDestination for TextDestination {
             .iter()
             .map(|col| { col.name.clone() })
             .collect();
+        self.formatter.set_columns(&self.column_names);
         self.table.add_row(
             prettytable::Row::new(
                 self.column_names
@@ -70,29 +71,8 @@ impl DataDestination for TextDestination {
         for row in rows {
             //<column index, value, original length, truncated>
             let mut row_data: Vec<String> = Vec::with_capacity(self.column_names.len());
-            for col in row.iter() {
-                let content = match col {
-                    Value::U64(value) => value.to_string(),
-                    Value::I64(value) => value.to_string(),
-                    Value::U32(value) => value.to_string(),
-                    Value::I32(value) => value.to_string(),
-                    Value::U16(value) => value.to_string(),
-                    Value::I16(value) => value.to_string(),
-                    Value::U8(value) => value.to_string(),
-                    Value::I8(value) => value.to_string(),
-                    Value::F64(value) => value.to_string(),
-                    Value::F32(value) => value.to_string(),
-                    Value::String(value) => truncate_text_with_note(value.to_string(), self.truncate),
-                    Value::Bool(value) => value.to_string(),
-                    Value::Bytes(value) => escape_binary_data(&value),
-                    Value::None => "".to_string(),
-                    Value::Timestamp(value) => value.to_string(),
-                    Value::Date(date) => format!("{}", date.format("%Y-%m-%d")),
-                    Value::Time(time) => format!("{}", time.format("%H:%M:%S")),
-                    Value::DateTime(datetime) => format!("{}", datetime.format("%Y-%m-%d %H:%M:%S")),
-                   
-                    _ => panic!(format!("text: unsupported type: {:?}", col))
-                };
+            for (idx, col) in row.iter().enumerate() {
+                let content = self.formatter.format_column(idx, col);
                 row_data.push(content);
             }
 
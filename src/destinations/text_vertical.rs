@@ -7,12 +7,12 @@ use termcolor::WriteColor;
 
 
 use crate::commands::{ApplicationArguments, export::TextVerticalDestinationOptions, UseColor};
-use crate::definitions::{Value, Row, DataSourceBatchIterator, DataDestination};
+use crate::definitions::{Row, DataSourceBatchIterator, DataDestination};
 use crate::utils::fileorstdout::FileOrStdout;
-use crate::utils::{escape_binary_data, truncate_text_with_note};
+use crate::utils::formatter::ValueFormatter;
 
 pub struct TextVerticalDestination {
-    truncate: Option<u64>,
+    formatter: ValueFormatter,
     column_names: Vec<String>,
     use_color: bool,
     writer: FileOrStdout,
@@ -23,9 +23,9 @@ impl TextVerticalDestination {
 
     pub fn init(args: &ApplicationArguments, options: &TextVerticalDestinationOptions) -> TextVerticalDestination {
         let use_color = match args.color {
-            UseColor::Yes => true,
-            UseColor::No => false,
-            UseColor::Auto => options.filename == "-" && atty::is(atty::Stream::Stdout),
+            Some(UseColor::Yes) => true,
+            Some(UseColor::No) => false,
+            None | Some(UseColor::Auto) => options.filename == "-" && atty::is(atty::Stream::Stdout),
         };
         let writer = match options.filename.as_str() {
             "-" => FileOrStdout::ColorStdout(termcolor::StandardStream::stdout(if use_color { termcolor::ColorChoice::Always} else { termcolor::ColorChoice::Never })),
@@ -33,7 +33,7 @@ impl TextVerticalDestination {
         };
       
         TextVerticalDestination {
-            truncate: options.truncate,
+            formatter: ValueFormatter::init(&options.formatting, &options.truncate),
             sort_columns: options.sort_columns,
             column_names: vec![],
             use_color,
@@ -52,6 +52,7 @@ impl DataDestination for TextVerticalDestination {
             .iter()
             .map(|col| { col.name.clone() })
             .collect();
+        self.formatter.set_columns(&self.column_names);
     }
     fn add_rows(&mut self, rows: &[Row]) {
 
@@ -60,28 +61,7 @@ impl DataDestination for TextVerticalDestination {
             let mut row_data: Vec<(usize, String)> = Vec::with_capacity(self.column_names.len());
             self.writer.write_all(&"──────────\n".to_string().into_bytes()).unwrap();
             for (idx, col) in row.iter().enumerate() {
-                let content = match col {
-                    Value::U64(value) => value.to_string(),
-                    Value::I64(value) => value.to_string(),
-                    Value::U32(value) => value.to_string(),
-                    Value::I32(value) => value.to_string(),
-                    Value::U16(value) => value.to_string(),
-                    Value::I16(value) => value.to_string(),
-                    Value::U8(value) => value.to_string(),
-                    Value::I8(value) => value.to_string(),
-                    Value::F64(value) => value.to_string(),
-                    Value::F32(value) => value.to_string(),
-                    Value::String(value) => truncate_text_with_note(value.to_string(), self.truncate),
-                    Value::Bool(value) => value.to_string(),
-                    Value::Bytes(value) => escape_binary_data(&value),
-                    Value::None => "".to_string(),
-                    Value::Timestamp(value) => value.to_string(),
-                    Value::Date(date) => format!("{}", date.format("%Y-%m-%d")),
-                    Value::Time(time) => format!("{}", time.format("%H:%M:%S")),
-                    Value::DateTime(datetime) => format!("{}", datetime.format("%Y-%m-%d %H:%M:%S")),
-                   
-                    _ => panic!(format!("text-vertical: unsupported type: {:?}", col))
-                };
+                let content = self.formatter.format_column(idx, col);
                 row_data.push((idx, content));
             }
             if self.sort_columns {
@@ -11,11 +11,20 @@ pub mod sources;
 pub mod utils;
 
 use commands::{ApplicationArguments, Command};
+use commands::sources::{SourcesCommand, SourcesSubCommand};
 
 
 fn main() {
 
-    let args = ApplicationArguments::from_args();
+    //broken sources are reported also when arguments are wrong, since they are missing from subcommands.
+    //sources validate reports them itself
+    let parsed = ApplicationArguments::from_args_safe();
+    let validating = matches!(&parsed, Ok(ApplicationArguments { command: Command::Sources(SourcesCommand { command: SourcesSubCommand::Validate(_) }), .. }));
+    if !validating {
+        config::warn_about_broken_sources();
+    }
+    let mut args = parsed.unwrap_or_else(|e| e.exit());
+    args.update_from_settings(&config::read_settings());
     match args.command {
         Command::Export(ref export_cmd) => {
             commands::export::export(&args, &export_cmd);
@@ -29,5 +38,13 @@ fn main() {
         Command::Sources(ref sources_cmd) => {
             commands::sources::sources(&args, &sources_cmd);
         },
+        #[cfg(feature = "use_vault")]
+        Command::Vault(ref vault_cmd) => {
+            commands::vault::vault(&args, &vault_cmd);
+        },
+        #[cfg(unix)]
+        Command::Agent(ref agent_cmd) => {
+            commands::agent::agent(&args, &agent_cmd);
+        },
     }
 }
@@ -0,0 +1,270 @@
+use std::collections::VecDeque;
+use std::io::Read;
+
+use chrono;
+use csv;
+
+use crate::commands::export::CsvSourceOptions;
+use crate::definitions::{ColumnType, Value, Row, ColumnInfo, DataSource, DataSourceConnection, DataSourceBatchIterator};
+use crate::utils::encoding::{DecodingReader, TextEncoding};
+
+static DATETIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+];
+
+///parse type name used in --column-type
+pub fn parse_type_name(name: &str) -> Result<ColumnType, String> {
+    match name {
+        "int" => Ok(ColumnType::I64),
+        "float" => Ok(ColumnType::F64),
+        "bool" => Ok(ColumnType::Bool),
+        "date" => Ok(ColumnType::Date),
+        "datetime" => Ok(ColumnType::DateTime),
+        "string" => Ok(ColumnType::String),
+        _ => Err(format!("unknown column type: {} (expected int, float, bool, date, datetime or string)", name)),
+    }
+}
+
+///parse text to value of given type, None if it does not match the type
+pub fn parse_value(text: &str, column_type: &ColumnType) -> Option<Value> {
+    match column_type {
+        ColumnType::String => return Some(Value::String(text.to_string())),
+        _ if text.is_empty() => return Some(Value::None),
+        _ => {},
+    }
+    match column_type {
+        ColumnType::I64 => text.parse().ok().map(Value::I64),
+        ColumnType::F64 => text.parse().ok().map(Value::F64),
+        ColumnType::Bool => match text.to_lowercase().as_str() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        ColumnType::Date => chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").ok().map(Value::Date),
+        ColumnType::DateTime => DATETIME_FORMATS
+            .iter()
+            .filter_map(|format| chrono::NaiveDateTime::parse_from_str(text, format).ok())
+            .next()
+            .map(Value::DateTime),
+        _ => Some(Value::String(text.to_string())),
+    }
+}
+
+///find narrowest type matching all non-empty values
+pub fn infer_column_type<'a>(values: impl Iterator<Item=&'a str> + Clone) -> ColumnType {
+    let candidates = [ColumnType::Bool, ColumnType::I64, ColumnType::F64, ColumnType::Date, ColumnType::DateTime];
+    if values.clone().all(|value| value.is_empty()) {
+        return ColumnType::String;
+    }
+    for candidate in candidates.iter() {
+        if values.clone().all(|value| parse_value(value, candidate).is_some()) {
+            return candidate.clone();
+        }
+    }
+    ColumnType::String
+}
+
+
+pub struct CsvSource {
+    options: CsvSourceOptions,
+}
+
+pub struct CsvSourceConnection<'c> {
+    source: &'c CsvSource,
+}
+
+pub struct CsvSourceBatchIterator<'i> {
+    batch_size: u64,
+    columns: Vec<ColumnInfo>,
+    reader: csv::Reader<DecodingReader<Box<dyn Read>>>,
+    //records read to infer column types, returned before the rest of the file
+    sample: VecDeque<csv::StringRecord>,
+    source: &'i CsvSource,
+}
+
+impl CsvSource {
+    pub fn init(csv_options: &CsvSourceOptions) -> CsvSource {
+        CsvSource { options: csv_options.to_owned() }
+    }
+
+    fn open(&self) -> csv::Reader<DecodingReader<Box<dyn Read>>> {
+        let input: Box<dyn Read> = match self.options.filename.as_str() {
+            "-" => Box::new(std::io::stdin()),
+            filename => match std::fs::File::open(filename) {
+                Ok(file) => Box::new(file),
+                Err(e) => {
+                    eprintln!("Error: could not open {}: {}", filename, e);
+                    std::process::exit(1);
+                }
+            }
+        };
+        let delimiter = match self.options.delimiter {
+            Some(delimiter) => delimiter,
+            None if self.options.filename.ends_with(".tsv") => b'\t',
+            None => b',',
+        };
+        let mut builder = csv::ReaderBuilder::new();
+        builder
+            .delimiter(delimiter)
+            .quote(self.options.quote)
+            .has_headers(!self.options.no_headers)
+            .flexible(true);
+        if let Some(escape) = self.options.escape {
+            builder.double_quote(false).escape(Some(escape));
+        }
+        builder.from_reader(DecodingReader::new(input, TextEncoding::from_name(&self.options.encoding).unwrap()))
+    }
+}
+
+
+impl <'c, 'i> DataSource<'c, 'i, CsvSourceConnection<'c>, CsvSourceBatchIterator<'i>> for CsvSource
+where 'c: 'i,
+{
+    fn connect(&'c self) -> CsvSourceConnection<'c>
+    {
+        CsvSourceConnection {
+            source: &self,
+        }
+    }
+
+    fn get_type_name(&self) -> String {"csv".to_string()}
+    fn get_name(&self) -> String { "csv".to_string() }
+}
+
+impl <'c, 'i>DataSourceConnection<'i, CsvSourceBatchIterator<'i>> for CsvSourceConnection<'c>
+where 'c: 'i,
+{
+    ///csv source has no query language, whole file is returned
+    fn batch_iterator(&'i mut self, _query: &str, batch_size: u64) -> CsvSourceBatchIterator<'i>
+    {
+        let options = &self.source.options;
+        let mut reader = self.source.open();
+        let mut sample = VecDeque::new();
+        for record in reader.records().take(options.infer_rows) {
+            match record {
+                Ok(record) => sample.push_back(record),
+                Err(e) => {
+                    eprintln!("Error: could not read {}: {}", options.filename, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        let column_count = sample.iter().map(|record| record.len()).max().unwrap_or(0);
+        let names: Vec<String> = if options.no_headers {
+            (1..=column_count).map(|idx| format!("column_{}", idx)).collect()
+        } else {
+            match reader.headers() {
+                Ok(headers) => headers.iter().map(|name| name.to_string()).collect(),
+                Err(e) => {
+                    eprintln!("Error: could not read {}: {}", options.filename, e);
+                    std::process::exit(1);
+                }
+            }
+        };
+        for (name, _) in options.column_type.iter() {
+            if !names.contains(name) {
+                eprintln!("Error: --column-type given for unknown column: {}", name);
+                std::process::exit(1);
+            }
+        }
+        let columns = names
+            .into_iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                let data_type = match options.column_type.iter().find(|(column, _)| column == &name) {
+                    Some((_, column_type)) => column_type.clone(),
+                    None => infer_column_type(sample.iter().map(|record| record.get(idx).unwrap_or(""))),
+                };
+                ColumnInfo { name, data_type }
+            })
+            .collect();
+
+        CsvSourceBatchIterator {
+            batch_size,
+            columns,
+            reader,
+            sample,
+            source: self.source,
+        }
+    }
+}
+
+
+impl <'i>CsvSourceBatchIterator<'i> {
+
+    ///values that do not match column type are kept as text
+    fn record_to_row(&self, record: &csv::StringRecord) -> Row {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(idx, column)| {
+                let text = record.get(idx).unwrap_or("");
+                parse_value(text, &column.data_type).unwrap_or_else(|| Value::String(text.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl <'i>DataSourceBatchIterator for CsvSourceBatchIterator<'i>
+{
+    fn get_column_info(&self) -> Vec<ColumnInfo> {
+        self.columns.clone()
+    }
+
+    fn get_count(&self) -> Option<u64> {
+        None
+    }
+
+    fn next(&mut self) -> Option<Vec<Row>>
+    {
+        let mut rows = vec![];
+        let mut record = csv::StringRecord::new();
+        while rows.len() < self.batch_size as usize {
+            if let Some(record) = self.sample.pop_front() {
+                rows.push(self.record_to_row(&record));
+                continue;
+            }
+            match self.reader.read_record(&mut record) {
+                Ok(true) => rows.push(self.record_to_row(&record)),
+                Ok(false) => break,
+                Err(e) => {
+                    eprintln!("Error: could not read {}: {}", self.source.options.filename, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        if !rows.is_empty() {
+            Some(rows)
+        } else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::infer_column_type;
+    use crate::definitions::ColumnType;
+
+    #[test]
+    fn test_infer_column_type() {
+        let cases: Vec<(Vec<&str>, ColumnType)> = vec![
+            (vec!["1", "", "-20"], ColumnType::I64),
+            (vec!["1", "2.5"], ColumnType::F64),
+            (vec!["true", "False"], ColumnType::Bool),
+            (vec!["2020-01-31", ""], ColumnType::Date),
+            (vec!["2020-01-31 10:00:00", "2020-01-31T10:00:00.5"], ColumnType::DateTime),
+            (vec!["1", "x"], ColumnType::String),
+            (vec!["", ""], ColumnType::String),
+        ];
+        for (values, expected) in cases {
+            assert_eq!(format!("{:?}", infer_column_type(values.into_iter())), format!("{:?}", expected));
+        }
+    }
+
+}
@@ -0,0 +1,302 @@
+use duckdb::{self, arrow::array::{self, Array, ArrayRef, StructArray}, arrow::datatypes::{DataType, TimeUnit}};
+use duckdb::arrow::util::display::array_value_to_string;
+
+use crate::commands::{common::DuckdbConfigOptions, export::DuckdbSourceOptions};
+use crate::definitions::{ColumnType, Value, Row, ColumnInfo, DataSource, DataSourceConnection, DataSourceBatchIterator};
+use crate::utils::{params::{bind_params, ParamValue}, report_query_error, sql::{split_setup, split_statements, Dialect}};
+
+pub trait GetDuckdbConnectionParams {
+    fn get_filename(&self) -> &Option<String>;
+    fn get_init(&self) -> &Vec<String>;
+}
+
+impl GetDuckdbConnectionParams for DuckdbSourceOptions {
+    fn get_filename(&self) -> &Option<String> { &self.filename }
+    fn get_init(&self) -> &Vec<String> { &self.init }
+}
+
+impl GetDuckdbConnectionParams for DuckdbConfigOptions {
+    fn get_filename(&self) -> &Option<String> { &self.filename }
+    fn get_init(&self) -> &Vec<String> { &self.init }
+}
+
+pub fn try_establish_duckdb_connection(options: &dyn GetDuckdbConnectionParams) -> Result<duckdb::Connection, String> {
+    let connection = match options.get_filename() {
+        Some(filename) => duckdb::Connection::open(filename),
+        None => duckdb::Connection::open_in_memory(),
+    };
+    connection.map_err(|e| format!("could not open duckdb database: {}", e))
+}
+
+pub fn establish_duckdb_connection(options: &dyn GetDuckdbConnectionParams) -> duckdb::Connection {
+    match try_establish_duckdb_connection(options) {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+///map arrow type of result column to column type. Types without equivalent are exported as text
+pub fn duckdb_column_type(data_type: &DataType) -> ColumnType {
+    match data_type {
+        DataType::Boolean => ColumnType::Bool,
+        DataType::Int8 => ColumnType::I8,
+        DataType::Int16 => ColumnType::I16,
+        DataType::Int32 => ColumnType::I32,
+        DataType::Int64 => ColumnType::I64,
+        DataType::UInt8 => ColumnType::U8,
+        DataType::UInt16 => ColumnType::U16,
+        DataType::UInt32 => ColumnType::U32,
+        DataType::UInt64 => ColumnType::U64,
+        DataType::Float32 => ColumnType::F32,
+        DataType::Float64 => ColumnType::F64,
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => ColumnType::Bytes,
+        DataType::Date32 => ColumnType::Date,
+        DataType::Time64(TimeUnit::Microsecond) => ColumnType::Time,
+        DataType::Timestamp(_, _) => ColumnType::DateTime,
+        DataType::Decimal128(_, _) => ColumnType::Decimal,
+        _ => ColumnType::String,
+    }
+}
+
+fn downcast<T: 'static>(column: &ArrayRef) -> &T {
+    column.as_any().downcast_ref::<T>().unwrap()
+}
+
+///read value from arrow column, falling back to its text representation
+pub fn arrow_value(column: &ArrayRef, row: usize) -> Value {
+    if column.is_null(row) {
+        return Value::None;
+    }
+    let value = match column.data_type() {
+        DataType::Boolean => Some(Value::Bool(downcast::<array::BooleanArray>(column).value(row))),
+        DataType::Int8 => Some(Value::I8(downcast::<array::Int8Array>(column).value(row))),
+        DataType::Int16 => Some(Value::I16(downcast::<array::Int16Array>(column).value(row))),
+        DataType::Int32 => Some(Value::I32(downcast::<array::Int32Array>(column).value(row))),
+        DataType::Int64 => Some(Value::I64(downcast::<array::Int64Array>(column).value(row))),
+        DataType::UInt8 => Some(Value::U8(downcast::<array::UInt8Array>(column).value(row))),
+        DataType::UInt16 => Some(Value::U16(downcast::<array::UInt16Array>(column).value(row))),
+        DataType::UInt32 => Some(Value::U32(downcast::<array::UInt32Array>(column).value(row))),
+        DataType::UInt64 => Some(Value::U64(downcast::<array::UInt64Array>(column).value(row))),
+        DataType::Float32 => Some(Value::F32(downcast::<array::Float32Array>(column).value(row))),
+        DataType::Float64 => Some(Value::F64(downcast::<array::Float64Array>(column).value(row))),
+        DataType::Utf8 => Some(Value::String(downcast::<array::StringArray>(column).value(row).to_string())),
+        DataType::LargeUtf8 => Some(Value::String(downcast::<array::LargeStringArray>(column).value(row).to_string())),
+        DataType::Binary => Some(Value::Bytes(downcast::<array::BinaryArray>(column).value(row).to_vec())),
+        DataType::LargeBinary => Some(Value::Bytes(downcast::<array::LargeBinaryArray>(column).value(row).to_vec())),
+        DataType::FixedSizeBinary(_) => Some(Value::Bytes(downcast::<array::FixedSizeBinaryArray>(column).value(row).to_vec())),
+        DataType::Date32 => downcast::<array::Date32Array>(column).value_as_date(row).map(Value::Date),
+        DataType::Time64(TimeUnit::Microsecond) => downcast::<array::Time64MicrosecondArray>(column).value_as_time(row).map(Value::Time),
+        DataType::Timestamp(TimeUnit::Second, _) => downcast::<array::TimestampSecondArray>(column).value_as_datetime(row).map(Value::DateTime),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => downcast::<array::TimestampMillisecondArray>(column).value_as_datetime(row).map(Value::DateTime),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => downcast::<array::TimestampMicrosecondArray>(column).value_as_datetime(row).map(Value::DateTime),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => downcast::<array::TimestampNanosecondArray>(column).value_as_datetime(row).map(Value::DateTime),
+        _ => None,
+    };
+    match value {
+        Some(value) => value,
+        None => match array_value_to_string(column, row) {
+            Ok(text) => Value::String(text),
+            Err(e) => Value::String(format!("<{}>", e)),
+        }
+    }
+}
+
+
+pub struct DuckdbSource {
+    options: DuckdbSourceOptions,
+}
+
+pub struct DuckdbSourceConnection<'c> {
+    connection: duckdb::Connection,
+    source: &'c DuckdbSource,
+}
+
+pub struct DuckdbSourceBatchIterator<'i> {
+    batch_size: u64,
+    columns: Vec<ColumnInfo>,
+    statement: duckdb::Statement<'i>,
+    //results are fetched from duckdb in chunks of few thousand rows
+    chunk: Option<StructArray>,
+    position: usize,
+}
+
+impl DuckdbSource {
+    pub fn init(duckdb_options: &DuckdbSourceOptions) -> DuckdbSource {
+        DuckdbSource { options: duckdb_options.to_owned() }
+    }
+}
+
+
+impl <'c, 'i> DataSource<'c, 'i, DuckdbSourceConnection<'c>, DuckdbSourceBatchIterator<'i>> for DuckdbSource
+where 'c: 'i,
+{
+    fn connect(&'c self) -> DuckdbSourceConnection<'c>
+    {
+        let connection = establish_duckdb_connection(&self.options);
+        for sql in self.options.init.iter() {
+            if let Err(e) = connection.execute_batch(sql) {
+                report_query_error(&sql, &format!("{}", e));
+                std::process::exit(1);
+            }
+        }
+
+        DuckdbSourceConnection {
+            connection,
+            source: &self,
+        }
+    }
+
+    fn get_type_name(&self) -> String {"duckdb".to_string()}
+    fn get_name(&self) -> String { "duckdb".to_string() }
+}
+
+impl <'c> DuckdbSourceConnection<'c> {
+
+    ///prepare statement, bind values of its :name placeholders and run it
+    fn execute_statement(&self, query: &str) -> duckdb::Statement<'_> {
+        let (query, bound_params) = match bind_params(query, &self.source.options.params, Dialect::Postgres, true, |position, _| format!("${}", position)) {
+            Ok(v) => v,
+            Err(e) => {
+                report_query_error(query, &e);
+                std::process::exit(1);
+            }
+        };
+        let mut statement = match self.connection.prepare(&query) {
+            Ok(statement) => statement,
+            Err(e) => {
+                report_query_error(&query, &format!("{}", e));
+                std::process::exit(1);
+            }
+        };
+        for (idx, param) in bound_params.iter().enumerate() {
+            let result = match &param.value {
+                ParamValue::Int(value) => statement.raw_bind_parameter(idx + 1, value),
+                ParamValue::Float(value) => statement.raw_bind_parameter(idx + 1, value),
+                ParamValue::Bool(value) => statement.raw_bind_parameter(idx + 1, value),
+                ParamValue::String(value) => statement.raw_bind_parameter(idx + 1, value),
+                ParamValue::Null => statement.raw_bind_parameter(idx + 1, duckdb::types::Null),
+            };
+            if let Err(e) = result {
+                report_query_error(&query, &format!("{}", e));
+                std::process::exit(1);
+            }
+        }
+        if let Err(e) = statement.raw_execute() {
+            report_query_error(&query, &format!("{}", e));
+            std::process::exit(1);
+        }
+        statement
+    }
+}
+
+impl <'c, 'i>DataSourceConnection<'i, DuckdbSourceBatchIterator<'i>> for DuckdbSourceConnection<'c>
+where 'c: 'i,
+{
+    fn execute(&mut self, sql: &str) {
+        for statement in split_statements(sql, Dialect::Postgres) {
+            self.execute_statement(&statement);
+        }
+    }
+
+    fn batch_iterator(&'i mut self, query: &str, batch_size: u64) -> DuckdbSourceBatchIterator<'i>
+    {
+        //all statements but last one prepare data for it
+        let (setup, query) = split_setup(query, Dialect::Postgres);
+        for statement in setup.iter() {
+            self.execute(statement);
+        }
+        let statement = self.execute_statement(&query);
+        let columns = statement
+            .column_names()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, name)| ColumnInfo { name, data_type: duckdb_column_type(&statement.column_type(idx)) })
+            .collect();
+
+        DuckdbSourceBatchIterator {
+            batch_size,
+            columns,
+            statement,
+            chunk: None,
+            position: 0,
+        }
+    }
+}
+
+
+impl <'i>DataSourceBatchIterator for DuckdbSourceBatchIterator<'i>
+{
+    fn get_column_info(&self) -> Vec<ColumnInfo> {
+        self.columns.clone()
+    }
+
+    fn get_count(&self) -> Option<u64> {
+        None
+    }
+
+    fn next(&mut self) -> Option<Vec<Row>>
+    {
+        let mut rows = vec![];
+        while rows.len() < self.batch_size as usize {
+            let chunk = match &self.chunk {
+                Some(chunk) if self.position < chunk.len() => chunk,
+                _ => match self.statement.step() {
+                    Some(chunk) => {
+                        self.chunk = Some(chunk);
+                        self.position = 0;
+                        continue;
+                    },
+                    None => break,
+                }
+            };
+            rows.push(
+                chunk.columns().iter().map(|column| arrow_value(column, self.position)).collect()
+            );
+            self.position += 1;
+        }
+        if !rows.is_empty() {
+            Some(rows)
+        } else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use duckdb;
+
+    use super::{arrow_value, duckdb_column_type};
+    use crate::definitions::ColumnType;
+
+    #[test]
+    fn test_arrow_value() {
+        let connection = duckdb::Connection::open_in_memory().unwrap();
+        let mut statement = connection.prepare("
+            select
+                1::tinyint, 2::ubigint, 'x', 1.5::decimal(10, 2),
+                date '2020-01-31', timestamp '2020-01-31 10:00:00.5', time '10:00:00',
+                null::integer, [1, 2]
+        ").unwrap();
+        statement.raw_execute().unwrap();
+        let types: Vec<ColumnType> = (0..9).map(|idx| duckdb_column_type(&statement.column_type(idx))).collect();
+        assert_eq!(types, vec![
+            ColumnType::I8, ColumnType::U64, ColumnType::String, ColumnType::Decimal,
+            ColumnType::Date, ColumnType::DateTime, ColumnType::Time,
+            ColumnType::I32, ColumnType::String,
+        ]);
+        let chunk = statement.step().unwrap();
+        let values: Vec<String> = chunk.columns().iter().map(|column| format!("{:?}", arrow_value(column, 0))).collect();
+        assert_eq!(values, vec![
+            "I8(1)", "U64(2)", "String(\"x\")", "String(\"1.50\")",
+            "Date(2020-01-31)", "DateTime(2020-01-31T10:00:00.500)", "Time(10:00:00)",
+            "None", "String(\"[1, 2]\")",
+        ]);
+    }
+
+}
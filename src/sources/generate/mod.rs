@@ -0,0 +1,258 @@
+use chrono::{self, TimeZone};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::commands::export::GenerateSourceOptions;
+use crate::definitions::{ColumnType, Value, Row, ColumnInfo, DataSource, DataSourceConnection, DataSourceBatchIterator};
+
+
+const FIRST_NAMES: [&str; 12] = ["Alice", "Bob", "Carol", "Dave", "Eve", "Frank", "Grace", "Heidi", "Ivan", "Judy", "Mallory", "Zoë"];
+const LAST_NAMES: [&str; 10] = ["Smith", "Jones", "Nowak", "Müller", "García", "O'Brien", "Kowalski", "Dubois", "Rossi", "Tanaka"];
+
+///generator names and types of values they produce
+pub const GENERATORS: [(&str, ColumnType); 22] = [
+    ("serial", ColumnType::U64),
+    ("u64", ColumnType::U64),
+    ("i64", ColumnType::I64),
+    ("u32", ColumnType::U32),
+    ("i32", ColumnType::I32),
+    ("u16", ColumnType::U16),
+    ("i16", ColumnType::I16),
+    ("u8", ColumnType::U8),
+    ("i8", ColumnType::I8),
+    ("f64", ColumnType::F64),
+    ("f32", ColumnType::F32),
+    ("string", ColumnType::String),
+    ("name", ColumnType::String),
+    ("bytes", ColumnType::Bytes),
+    ("bool", ColumnType::Bool),
+    ("date", ColumnType::Date),
+    ("datetime", ColumnType::DateTime),
+    ("time", ColumnType::Time),
+    ("timestamp", ColumnType::Timestamp),
+    ("decimal", ColumnType::Decimal),
+    ("json", ColumnType::JSON),
+    ("null", ColumnType::None),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratedColumn {
+    pub name: String,
+    pub generator: String,
+    pub data_type: ColumnType,
+    pub nullable: bool,
+}
+
+///parse column spec: comma separated name:generator pairs, generator followed by ? produces some nulls
+pub fn parse_columns(spec: &str) -> Result<Vec<GeneratedColumn>, String> {
+    spec
+        .split(',')
+        .map(|column| {
+            let mut parts = column.rsplitn(2, ':');
+            let generator = parts.next().unwrap();
+            let name = match parts.next() {
+                Some(name) if !name.is_empty() => name,
+                _ => return Err(format!("expected name:generator, got: {}", column)),
+            };
+            let (generator, nullable) = match generator.strip_suffix('?') {
+                Some(generator) => (generator, true),
+                None => (generator, false),
+            };
+            match GENERATORS.iter().find(|(generator_name, _)| *generator_name == generator) {
+                Some((generator, data_type)) => Ok(GeneratedColumn {
+                    name: name.to_string(),
+                    generator: generator.to_string(),
+                    data_type: data_type.clone(),
+                    nullable,
+                }),
+                None => Err(format!(
+                    "unknown generator: {} (expected one of: {})",
+                    generator,
+                    GENERATORS.iter().map(|(name, _)| *name).collect::<Vec<&str>>().join(", ")
+                )),
+            }
+        })
+        .collect()
+}
+
+fn random_datetime(rng: &mut StdRng) -> chrono::NaiveDateTime {
+    //between 1970-01-01 and 2038-01-19
+    chrono::Utc.timestamp_opt(rng.gen_range(0..i32::MAX as i64), rng.gen_range(0..1_000_000) * 1000).unwrap().naive_utc()
+}
+
+pub fn generate_value(column: &GeneratedColumn, row_number: u64, rng: &mut StdRng) -> Value {
+    if column.nullable && rng.gen_bool(0.1) {
+        return Value::None;
+    }
+    match column.generator.as_str() {
+        "serial" => Value::U64(row_number),
+        "u64" => Value::U64(rng.gen()),
+        "i64" => Value::I64(rng.gen()),
+        "u32" => Value::U32(rng.gen()),
+        "i32" => Value::I32(rng.gen()),
+        "u16" => Value::U16(rng.gen()),
+        "i16" => Value::I16(rng.gen()),
+        "u8" => Value::U8(rng.gen()),
+        "i8" => Value::I8(rng.gen()),
+        "f64" => Value::F64(rng.gen_range(-1_000_000.0..1_000_000.0)),
+        "f32" => Value::F32(rng.gen_range(-1000.0..1000.0)),
+        "string" => {
+            let length = rng.gen_range(0..=20);
+            Value::String((0..length).map(|_| rng.sample(rand::distributions::Alphanumeric) as char).collect())
+        },
+        "name" => Value::String(format!(
+            "{} {}",
+            FIRST_NAMES[rng.gen_range(0..FIRST_NAMES.len())],
+            LAST_NAMES[rng.gen_range(0..LAST_NAMES.len())]
+        )),
+        "bytes" => {
+            let length = rng.gen_range(0..=32);
+            Value::Bytes((0..length).map(|_| rng.gen::<u8>()).collect())
+        },
+        "bool" => Value::Bool(rng.gen()),
+        "date" => Value::Date(random_datetime(rng).date()),
+        "datetime" => Value::DateTime(random_datetime(rng)),
+        "time" => Value::Time(random_datetime(rng).time()),
+        "timestamp" => Value::Timestamp(rng.gen_range(0..i32::MAX as u64)),
+        "decimal" => Value::String(format!("{}.{:02}", rng.gen_range(-100_000..100_000), rng.gen_range(0..100))),
+        "json" => Value::JSON(format!(
+            "{{\"id\": {}, \"score\": {}, \"tags\": [{}]}}",
+            row_number,
+            rng.gen_range(0..100),
+            (0..rng.gen_range(0..4)).map(|tag| format!("\"tag{}\"", tag)).collect::<Vec<String>>().join(", ")
+        )),
+        _ => Value::None,
+    }
+}
+
+
+pub struct GenerateSource {
+    options: GenerateSourceOptions,
+    columns: Vec<GeneratedColumn>,
+}
+
+pub struct GenerateSourceConnection<'c> {
+    source: &'c GenerateSource,
+}
+
+pub struct GenerateSourceBatchIterator<'i> {
+    batch_size: u64,
+    row_number: u64,
+    rng: StdRng,
+    source: &'i GenerateSource,
+}
+
+impl GenerateSource {
+    pub fn init(generate_options: &GenerateSourceOptions) -> GenerateSource {
+        let columns = match parse_columns(&generate_options.columns) {
+            Ok(columns) => columns,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        GenerateSource { options: generate_options.to_owned(), columns }
+    }
+}
+
+
+impl <'c, 'i> DataSource<'c, 'i, GenerateSourceConnection<'c>, GenerateSourceBatchIterator<'i>> for GenerateSource
+where 'c: 'i,
+{
+    fn connect(&'c self) -> GenerateSourceConnection<'c>
+    {
+        GenerateSourceConnection {
+            source: &self,
+        }
+    }
+
+    fn get_type_name(&self) -> String {"generate".to_string()}
+    fn get_name(&self) -> String { "generate".to_string() }
+}
+
+impl <'c, 'i>DataSourceConnection<'i, GenerateSourceBatchIterator<'i>> for GenerateSourceConnection<'c>
+where 'c: 'i,
+{
+    ///rows are generated from column spec, query is not used
+    fn batch_iterator(&'i mut self, _query: &str, batch_size: u64) -> GenerateSourceBatchIterator<'i>
+    {
+        GenerateSourceBatchIterator {
+            batch_size,
+            row_number: 0,
+            rng: match self.source.options.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            source: self.source,
+        }
+    }
+}
+
+
+impl <'i>DataSourceBatchIterator for GenerateSourceBatchIterator<'i>
+{
+    fn get_column_info(&self) -> Vec<ColumnInfo> {
+        self.source.columns
+            .iter()
+            .map(|column| ColumnInfo { name: column.name.clone(), data_type: column.data_type.clone() })
+            .collect()
+    }
+
+    fn get_count(&self) -> Option<u64> {
+        Some(self.source.options.rows)
+    }
+
+    fn next(&mut self) -> Option<Vec<Row>>
+    {
+        let mut rows = vec![];
+        while rows.len() < self.batch_size as usize && self.row_number < self.source.options.rows {
+            self.row_number += 1;
+            let row_number = self.row_number;
+            let rng = &mut self.rng;
+            rows.push(
+                self.source.columns.iter().map(|column| generate_value(column, row_number, rng)).collect()
+            );
+        }
+        if !rows.is_empty() {
+            Some(rows)
+        } else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::{generate_value, parse_columns, GeneratedColumn};
+    use crate::definitions::ColumnType;
+
+    #[test]
+    fn test_parse_columns() {
+        let columns = parse_columns("id:serial,created:datetime,a:b:i32?").unwrap();
+        assert_eq!(columns, vec![
+            GeneratedColumn { name: "id".to_string(), generator: "serial".to_string(), data_type: ColumnType::U64, nullable: false },
+            GeneratedColumn { name: "created".to_string(), generator: "datetime".to_string(), data_type: ColumnType::DateTime, nullable: false },
+            GeneratedColumn { name: "a:b".to_string(), generator: "i32".to_string(), data_type: ColumnType::I32, nullable: true },
+        ]);
+        assert!(parse_columns("id").is_err());
+        assert!(parse_columns("id:nope").is_err());
+    }
+
+    #[test]
+    fn test_generate_value_seed() {
+        let columns = parse_columns("id:serial,name:name,score:f64?,blob:bytes,doc:json").unwrap();
+        let generate = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (1..=20)
+                .map(|row_number| columns.iter().map(|column| format!("{:?}", generate_value(column, row_number, &mut rng))).collect::<Vec<String>>())
+                .collect::<Vec<Vec<String>>>()
+        };
+        assert_eq!(generate(7), generate(7));
+        assert_ne!(generate(7), generate(8));
+        assert_eq!(generate(7)[2][0], "U64(3)");
+    }
+
+}
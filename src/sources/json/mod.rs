@@ -0,0 +1,351 @@
+use std::collections::{HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+
+use serde_json;
+
+use crate::commands::export::JsonSourceOptions;
+use crate::definitions::{ColumnType, Value, Row, ColumnInfo, DataSource, DataSourceConnection, DataSourceBatchIterator};
+
+
+///reads consecutive json values from top-level array or from newline delimited json,
+///without loading whole input into memory
+pub struct JsonValueReader<R: BufRead> {
+    inner: R,
+    peeked: Option<u8>,
+    started: bool,
+    finished: bool,
+    line: u64,
+}
+
+impl <R: BufRead> JsonValueReader<R> {
+
+    pub fn new(inner: R) -> JsonValueReader<R> {
+        JsonValueReader { inner, peeked: None, started: false, finished: false, line: 1 }
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, String> {
+        if let Some(byte) = self.peeked.take() {
+            return Ok(Some(byte));
+        }
+        let byte = match self.inner.fill_buf() {
+            Ok(buffer) if buffer.is_empty() => return Ok(None),
+            Ok(buffer) => buffer[0],
+            Err(e) => return Err(e.to_string()),
+        };
+        self.inner.consume(1);
+        if byte == b'\n' {
+            self.line += 1;
+        }
+        Ok(Some(byte))
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line, message)
+    }
+
+    ///read raw bytes of next value, None if there are no more values
+    fn next_raw_value(&mut self) -> Result<Option<Vec<u8>>, String> {
+        if self.finished {
+            return Ok(None);
+        }
+        let first = loop {
+            match self.next_byte()? {
+                None => { self.finished = true; return Ok(None) },
+                Some(b'[') if !self.started => { self.started = true; },
+                Some(b']') => { self.finished = true; return Ok(None) },
+                Some(byte) if byte.is_ascii_whitespace() || byte == b',' => {},
+                Some(byte) => break byte,
+            }
+        };
+        self.started = true;
+        let mut raw = vec![first];
+        let mut depth = match first {
+            b'{' | b'[' => 1,
+            _ => 0,
+        };
+        let mut in_string = first == b'"';
+        let mut escaped = false;
+        loop {
+            if depth == 0 && !in_string && raw.len() > 1 && (first == b'"' || first == b'{' || first == b'[') {
+                break;
+            }
+            let byte = match self.next_byte()? {
+                Some(byte) => byte,
+                None if depth == 0 && !in_string => break,
+                None => return Err(self.error("unexpected end of input")),
+            };
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if byte == b'\\' {
+                    escaped = true;
+                } else if byte == b'"' {
+                    in_string = false;
+                }
+            } else {
+                match byte {
+                    b'"' => in_string = true,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' if depth > 0 => depth -= 1,
+                    //end of scalar value
+                    b',' | b']' | b' ' | b'\t' | b'\r' | b'\n' if depth == 0 => {
+                        self.peeked = Some(byte);
+                        break;
+                    },
+                    _ => {},
+                }
+            }
+            raw.push(byte);
+        }
+        Ok(Some(raw))
+    }
+
+    pub fn next_value(&mut self) -> Result<Option<serde_json::Value>, String> {
+        match self.next_raw_value()? {
+            None => Ok(None),
+            Some(raw) => serde_json::from_slice(&raw).map(Some).map_err(|e| self.error(&e.to_string())),
+        }
+    }
+}
+
+
+///move fields of nested objects to top level, joining keys with separator
+pub fn flatten_object(prefix: &str, object: serde_json::Map<String, serde_json::Value>, separator: &str, result: &mut serde_json::Map<String, serde_json::Value>) {
+    for (key, value) in object {
+        let name = if prefix.is_empty() { key } else { format!("{}{}{}", prefix, separator, key) };
+        match value {
+            serde_json::Value::Object(nested) => flatten_object(&name, nested, separator, result),
+            value => { result.insert(name, value); },
+        }
+    }
+}
+
+///find type matching all non-null values. Mixed types are exported as text
+pub fn infer_column_type<'a>(values: impl Iterator<Item=&'a serde_json::Value>) -> ColumnType {
+    let mut result: Option<ColumnType> = None;
+    for value in values {
+        let value_type = match value {
+            serde_json::Value::Null => continue,
+            serde_json::Value::Bool(_) => ColumnType::Bool,
+            serde_json::Value::Number(number) if number.is_i64() => ColumnType::I64,
+            serde_json::Value::Number(_) => ColumnType::F64,
+            serde_json::Value::String(_) => ColumnType::String,
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => ColumnType::JSON,
+        };
+        result = Some(match result {
+            None => value_type,
+            Some(column_type) if column_type == value_type => column_type,
+            Some(ColumnType::I64) | Some(ColumnType::F64) if value_type == ColumnType::I64 || value_type == ColumnType::F64 => ColumnType::F64,
+            Some(_) => return ColumnType::String,
+        });
+    }
+    result.unwrap_or(ColumnType::String)
+}
+
+///values that do not match column type are kept as text
+pub fn json_to_value(value: &serde_json::Value, column_type: &ColumnType) -> Value {
+    match (value, column_type) {
+        (serde_json::Value::Null, _) => Value::None,
+        (serde_json::Value::Bool(value), ColumnType::Bool) => Value::Bool(*value),
+        (serde_json::Value::Number(number), ColumnType::I64) if number.is_i64() => Value::I64(number.as_i64().unwrap()),
+        (serde_json::Value::Number(number), ColumnType::F64) => Value::F64(number.as_f64().unwrap()),
+        (value, ColumnType::JSON) => Value::JSON(value.to_string()),
+        (serde_json::Value::String(value), _) => Value::String(value.clone()),
+        (value, _) => Value::String(value.to_string()),
+    }
+}
+
+
+pub struct JsonSource {
+    options: JsonSourceOptions,
+}
+
+pub struct JsonSourceConnection<'c> {
+    source: &'c JsonSource,
+}
+
+pub struct JsonSourceBatchIterator<'i> {
+    batch_size: u64,
+    columns: Vec<ColumnInfo>,
+    reader: JsonValueReader<BufReader<Box<dyn Read>>>,
+    //objects read to build column list, returned before the rest of the file
+    sample: VecDeque<serde_json::Map<String, serde_json::Value>>,
+    unknown_columns: HashSet<String>,
+    source: &'i JsonSource,
+}
+
+impl JsonSource {
+    pub fn init(json_options: &JsonSourceOptions) -> JsonSource {
+        JsonSource { options: json_options.to_owned() }
+    }
+
+    fn open(&self) -> JsonValueReader<BufReader<Box<dyn Read>>> {
+        let input: Box<dyn Read> = match self.options.filename.as_str() {
+            "-" => Box::new(std::io::stdin()),
+            filename => match std::fs::File::open(filename) {
+                Ok(file) => Box::new(file),
+                Err(e) => {
+                    eprintln!("Error: could not open {}: {}", filename, e);
+                    std::process::exit(1);
+                }
+            }
+        };
+        JsonValueReader::new(BufReader::new(input))
+    }
+
+    ///read next object, flattening it if requested
+    fn read_object(&self, reader: &mut JsonValueReader<BufReader<Box<dyn Read>>>) -> Option<serde_json::Map<String, serde_json::Value>> {
+        match reader.next_value() {
+            Ok(None) => None,
+            Ok(Some(serde_json::Value::Object(object))) => {
+                if self.options.flatten {
+                    let mut flat = serde_json::Map::new();
+                    flatten_object("", object, &self.options.separator, &mut flat);
+                    Some(flat)
+                } else {
+                    Some(object)
+                }
+            },
+            Ok(Some(_)) => {
+                eprintln!("Error: {}: {}", self.options.filename, reader.error("expected json object"));
+                std::process::exit(1);
+            },
+            Err(e) => {
+                eprintln!("Error: {}: {}", self.options.filename, e);
+                std::process::exit(1);
+            },
+        }
+    }
+}
+
+
+impl <'c, 'i> DataSource<'c, 'i, JsonSourceConnection<'c>, JsonSourceBatchIterator<'i>> for JsonSource
+where 'c: 'i,
+{
+    fn connect(&'c self) -> JsonSourceConnection<'c>
+    {
+        JsonSourceConnection {
+            source: &self,
+        }
+    }
+
+    fn get_type_name(&self) -> String {"json".to_string()}
+    fn get_name(&self) -> String { "json".to_string() }
+}
+
+impl <'c, 'i>DataSourceConnection<'i, JsonSourceBatchIterator<'i>> for JsonSourceConnection<'c>
+where 'c: 'i,
+{
+    ///json source has no query language, whole file is returned
+    fn batch_iterator(&'i mut self, _query: &str, batch_size: u64) -> JsonSourceBatchIterator<'i>
+    {
+        let mut reader = self.source.open();
+        let mut sample = VecDeque::new();
+        while sample.len() < self.source.options.infer_rows {
+            match self.source.read_object(&mut reader) {
+                Some(object) => sample.push_back(object),
+                None => break,
+            }
+        }
+        //union of keys, in order of appearance
+        let mut names: Vec<String> = vec![];
+        for object in sample.iter() {
+            for key in object.keys() {
+                if !names.contains(key) {
+                    names.push(key.clone());
+                }
+            }
+        }
+        let columns = names
+            .into_iter()
+            .map(|name| {
+                let data_type = infer_column_type(sample.iter().filter_map(|object| object.get(&name)));
+                ColumnInfo { name, data_type }
+            })
+            .collect();
+
+        JsonSourceBatchIterator {
+            batch_size,
+            columns,
+            reader,
+            sample,
+            unknown_columns: HashSet::new(),
+            source: self.source,
+        }
+    }
+}
+
+
+impl <'i>JsonSourceBatchIterator<'i> {
+
+    fn object_to_row(&mut self, object: &serde_json::Map<String, serde_json::Value>) -> Row {
+        for key in object.keys() {
+            if !self.unknown_columns.contains(key) && !self.columns.iter().any(|column| &column.name == key) {
+                eprintln!("warning: skipping key {} which was not present in first {} objects", key, self.source.options.infer_rows);
+                self.unknown_columns.insert(key.clone());
+            }
+        }
+        self.columns
+            .iter()
+            .map(|column| match object.get(&column.name) {
+                Some(value) => json_to_value(value, &column.data_type),
+                None => Value::None,
+            })
+            .collect()
+    }
+}
+
+impl <'i>DataSourceBatchIterator for JsonSourceBatchIterator<'i>
+{
+    fn get_column_info(&self) -> Vec<ColumnInfo> {
+        self.columns.clone()
+    }
+
+    fn get_count(&self) -> Option<u64> {
+        None
+    }
+
+    fn next(&mut self) -> Option<Vec<Row>>
+    {
+        let mut rows = vec![];
+        while rows.len() < self.batch_size as usize {
+            let object = match self.sample.pop_front() {
+                Some(object) => object,
+                None => match self.source.read_object(&mut self.reader) {
+                    Some(object) => object,
+                    None => break,
+                }
+            };
+            rows.push(self.object_to_row(&object));
+        }
+        if !rows.is_empty() {
+            Some(rows)
+        } else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::JsonValueReader;
+
+    #[test]
+    fn test_json_value_reader() {
+        let inputs: Vec<&[u8]> = vec![
+            b" [{\"a\": \"x]}\"}, 1, {\"b\": [1, {}]} ] ",
+            b"{\"a\": \"x]}\"}\n1\n{\"b\": [1, {}]}\n",
+        ];
+        for input in inputs {
+            let mut reader = JsonValueReader::new(input);
+            let mut values = vec![];
+            while let Some(value) = reader.next_value().unwrap() {
+                values.push(value.to_string());
+            }
+            assert_eq!(values, vec!["{\"a\":\"x]}\"}", "1", "{\"b\":[1,{}]}"]);
+        }
+    }
+
+}
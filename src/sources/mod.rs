@@ -1,7 +1,19 @@
+#[cfg(feature = "use_csv")] 
+pub mod csv;
+#[cfg(feature = "use_duckdb")] 
+pub mod duckdb;
+#[cfg(feature = "use_generate")] 
+pub mod generate;
+#[cfg(feature = "use_json")] 
+pub mod json;
 #[cfg(feature = "use_mysql")] 
 pub mod mysql;
+#[cfg(feature = "use_parquet")] 
+pub mod parquet;
 #[cfg(feature = "use_postgres")] 
 pub mod postgres;
+#[cfg(feature = "use_calamine")] 
+pub mod spreadsheet;
 #[cfg(feature = "use_sqlite")] 
 pub mod sqlite;
 
@@ -10,8 +22,20 @@ use crate::definitions::{DataSource, DataSourceConnection, DataSourceBatchIterat
 
 
 pub enum Source {
+    #[cfg(feature = "use_csv")]
+    Csv(csv::CsvSource),
+    #[cfg(feature = "use_generate")]
+    Generate(generate::GenerateSource),
+    #[cfg(feature = "use_json")]
+    Json(json::JsonSource),
+    #[cfg(feature = "use_parquet")]
+    Parquet(parquet::ParquetSource),
+    #[cfg(feature = "use_calamine")]
+    SpreadSheet(spreadsheet::SpreadSheetSource),
     #[cfg(feature = "use_sqlite")]
     Sqlite(sqlite::SqliteSource),
+    #[cfg(feature = "use_duckdb")]
+    Duckdb(duckdb::DuckdbSource),
     #[cfg(feature = "use_mysql")]
     Mysql(mysql::MysqlSource),
     #[cfg(feature = "use_postgres")]
@@ -20,8 +44,20 @@ pub enum Source {
 
 
 pub enum SourceConnection<'c> {
+    #[cfg(feature = "use_csv")]
+    CsvConnection(csv::CsvSourceConnection<'c>),
+    #[cfg(feature = "use_generate")]
+    GenerateConnection(generate::GenerateSourceConnection<'c>),
+    #[cfg(feature = "use_json")]
+    JsonConnection(json::JsonSourceConnection<'c>),
+    #[cfg(feature = "use_parquet")]
+    ParquetConnection(parquet::ParquetSourceConnection<'c>),
+    #[cfg(feature = "use_calamine")]
+    SpreadSheetConnection(spreadsheet::SpreadSheetSourceConnection<'c>),
     #[cfg(feature = "use_sqlite")]
     SqliteConnection(sqlite::SqliteSourceConnection<'c>),
+    #[cfg(feature = "use_duckdb")]
+    DuckdbConnection(duckdb::DuckdbSourceConnection<'c>),
     #[cfg(feature = "use_mysql")]
     MysqlConnection(mysql::MysqlSourceConnection<'c>),
     #[cfg(feature = "use_postgres")]
@@ -30,8 +66,20 @@ pub enum SourceConnection<'c> {
 
 
 pub enum SourceBatchIterator<'i> {
+    #[cfg(feature = "use_csv")]
+    CsvBatchIterator(csv::CsvSourceBatchIterator<'i>),
+    #[cfg(feature = "use_generate")]
+    GenerateBatchIterator(generate::GenerateSourceBatchIterator<'i>),
+    #[cfg(feature = "use_json")]
+    JsonBatchIterator(json::JsonSourceBatchIterator<'i>),
+    #[cfg(feature = "use_parquet")]
+    ParquetBatchIterator(parquet::ParquetSourceBatchIterator<'i>),
+    #[cfg(feature = "use_calamine")]
+    SpreadSheetBatchIterator(spreadsheet::SpreadSheetSourceBatchIterator<'i>),
     #[cfg(feature = "use_sqlite")]
     SqliteBatchIterator(sqlite::SqliteSourceBatchIterator<'i>),
+    #[cfg(feature = "use_duckdb")]
+    DuckdbBatchIterator(duckdb::DuckdbSourceBatchIterator<'i>),
     #[cfg(feature = "use_mysql")]
     MysqlBatchIterator(mysql::MysqlSourceBatchIterator<'i>),
     #[cfg(feature = "use_postgres")]
@@ -43,8 +91,20 @@ impl <'c, 'i>DataSource<'c, 'i, SourceConnection<'c>, SourceBatchIterator<'i>> f
 where 'c: 'i {
     fn connect(&'c self) -> SourceConnection {
         match self {
+            #[cfg(feature = "use_csv")]
+            Source::Csv(csv_source) => SourceConnection::CsvConnection(csv_source.connect()), 
+            #[cfg(feature = "use_generate")]
+            Source::Generate(generate_source) => SourceConnection::GenerateConnection(generate_source.connect()), 
+            #[cfg(feature = "use_json")]
+            Source::Json(json_source) => SourceConnection::JsonConnection(json_source.connect()), 
+            #[cfg(feature = "use_parquet")]
+            Source::Parquet(parquet_source) => SourceConnection::ParquetConnection(parquet_source.connect()), 
+            #[cfg(feature = "use_calamine")]
+            Source::SpreadSheet(spreadsheet_source) => SourceConnection::SpreadSheetConnection(spreadsheet_source.connect()), 
             #[cfg(feature = "use_sqlite")]
             Source::Sqlite(sqlite_source) => SourceConnection::SqliteConnection(sqlite_source.connect()), 
+            #[cfg(feature = "use_duckdb")]
+            Source::Duckdb(duckdb_source) => SourceConnection::DuckdbConnection(duckdb_source.connect()), 
             #[cfg(feature = "use_mysql")]
             Source::Mysql(mysql_source) => SourceConnection::MysqlConnection(mysql_source.connect()), 
             #[cfg(feature = "use_postgres")]
@@ -54,8 +114,20 @@ where 'c: 'i {
 
     fn get_type_name(&self) -> String {
         match self {
+            #[cfg(feature = "use_csv")]
+            Source::Csv(csv_source) => csv_source.get_type_name(), 
+            #[cfg(feature = "use_generate")]
+            Source::Generate(generate_source) => generate_source.get_type_name(), 
+            #[cfg(feature = "use_json")]
+            Source::Json(json_source) => json_source.get_type_name(), 
+            #[cfg(feature = "use_parquet")]
+            Source::Parquet(parquet_source) => parquet_source.get_type_name(), 
+            #[cfg(feature = "use_calamine")]
+            Source::SpreadSheet(spreadsheet_source) => spreadsheet_source.get_type_name(), 
             #[cfg(feature = "use_sqlite")]
             Source::Sqlite(sqlite_source) => sqlite_source.get_type_name(), 
+            #[cfg(feature = "use_duckdb")]
+            Source::Duckdb(duckdb_source) => duckdb_source.get_type_name(), 
             #[cfg(feature = "use_mysql")]
             Source::Mysql(mysql_source) => mysql_source.get_type_name(), 
             #[cfg(feature = "use_postgres")]
@@ -65,8 +137,20 @@ where 'c: 'i {
 
     fn get_name(&self) -> String {
         match self {
+            #[cfg(feature = "use_csv")]
+            Source::Csv(csv_source) => csv_source.get_name(), 
+            #[cfg(feature = "use_generate")]
+            Source::Generate(generate_source) => generate_source.get_name(), 
+            #[cfg(feature = "use_json")]
+            Source::Json(json_source) => json_source.get_name(), 
+            #[cfg(feature = "use_parquet")]
+            Source::Parquet(parquet_source) => parquet_source.get_name(), 
+            #[cfg(feature = "use_calamine")]
+            Source::SpreadSheet(spreadsheet_source) => spreadsheet_source.get_name(), 
             #[cfg(feature = "use_sqlite")]
             Source::Sqlite(sqlite_source) => sqlite_source.get_name(), 
+            #[cfg(feature = "use_duckdb")]
+            Source::Duckdb(duckdb_source) => duckdb_source.get_name(), 
             #[cfg(feature = "use_mysql")]
             Source::Mysql(mysql_source) => mysql_source.get_name(), 
             #[cfg(feature = "use_postgres")]
@@ -79,14 +163,49 @@ where 'c: 'i {
 
 impl <'c, 'i>DataSourceConnection<'i, SourceBatchIterator<'i>> for SourceConnection<'c> {
 
-    fn batch_iterator(&'i mut self, batch_size: u64) -> SourceBatchIterator<'i> {
+    fn execute(&mut self, sql: &str) {
         match self {
+            #[cfg(feature = "use_csv")]
+            SourceConnection::CsvConnection(csv_connection) => csv_connection.execute(sql),
+            #[cfg(feature = "use_generate")]
+            SourceConnection::GenerateConnection(generate_connection) => generate_connection.execute(sql),
+            #[cfg(feature = "use_json")]
+            SourceConnection::JsonConnection(json_connection) => json_connection.execute(sql),
+            #[cfg(feature = "use_parquet")]
+            SourceConnection::ParquetConnection(parquet_connection) => parquet_connection.execute(sql),
+            #[cfg(feature = "use_calamine")]
+            SourceConnection::SpreadSheetConnection(spreadsheet_connection) => spreadsheet_connection.execute(sql),
             #[cfg(feature = "use_sqlite")]
-            SourceConnection::SqliteConnection(sqlite_connection) => SourceBatchIterator::SqliteBatchIterator((*sqlite_connection).batch_iterator(batch_size)), 
+            SourceConnection::SqliteConnection(sqlite_connection) => sqlite_connection.execute(sql),
+            #[cfg(feature = "use_duckdb")]
+            SourceConnection::DuckdbConnection(duckdb_connection) => duckdb_connection.execute(sql),
             #[cfg(feature = "use_mysql")]
-            SourceConnection::MysqlConnection(mysql_connection) => SourceBatchIterator::MysqlBatchIterator(mysql_connection.batch_iterator(batch_size)), 
+            SourceConnection::MysqlConnection(mysql_connection) => mysql_connection.execute(sql),
             #[cfg(feature = "use_postgres")]
-            SourceConnection::PostgresConnection(postgres_connection) => SourceBatchIterator::PostgresBatchIterator(postgres_connection.batch_iterator(batch_size)), 
+            SourceConnection::PostgresConnection(postgres_connection) => postgres_connection.execute(sql),
+        }
+    }
+
+    fn batch_iterator(&'i mut self, query: &str, batch_size: u64) -> SourceBatchIterator<'i> {
+        match self {
+            #[cfg(feature = "use_csv")]
+            SourceConnection::CsvConnection(csv_connection) => SourceBatchIterator::CsvBatchIterator(csv_connection.batch_iterator(query, batch_size)), 
+            #[cfg(feature = "use_generate")]
+            SourceConnection::GenerateConnection(generate_connection) => SourceBatchIterator::GenerateBatchIterator(generate_connection.batch_iterator(query, batch_size)), 
+            #[cfg(feature = "use_json")]
+            SourceConnection::JsonConnection(json_connection) => SourceBatchIterator::JsonBatchIterator(json_connection.batch_iterator(query, batch_size)), 
+            #[cfg(feature = "use_parquet")]
+            SourceConnection::ParquetConnection(parquet_connection) => SourceBatchIterator::ParquetBatchIterator(parquet_connection.batch_iterator(query, batch_size)), 
+            #[cfg(feature = "use_calamine")]
+            SourceConnection::SpreadSheetConnection(spreadsheet_connection) => SourceBatchIterator::SpreadSheetBatchIterator(spreadsheet_connection.batch_iterator(query, batch_size)), 
+            #[cfg(feature = "use_sqlite")]
+            SourceConnection::SqliteConnection(sqlite_connection) => SourceBatchIterator::SqliteBatchIterator((*sqlite_connection).batch_iterator(query, batch_size)), 
+            #[cfg(feature = "use_duckdb")]
+            SourceConnection::DuckdbConnection(duckdb_connection) => SourceBatchIterator::DuckdbBatchIterator(duckdb_connection.batch_iterator(query, batch_size)), 
+            #[cfg(feature = "use_mysql")]
+            SourceConnection::MysqlConnection(mysql_connection) => SourceBatchIterator::MysqlBatchIterator(mysql_connection.batch_iterator(query, batch_size)), 
+            #[cfg(feature = "use_postgres")]
+            SourceConnection::PostgresConnection(postgres_connection) => SourceBatchIterator::PostgresBatchIterator(postgres_connection.batch_iterator(query, batch_size)), 
         }
    
     }
@@ -97,8 +216,20 @@ impl <'c, 'i>DataSourceBatchIterator for SourceBatchIterator<'i> {
 
     fn get_column_info(&self) -> Vec<ColumnInfo> {
         match self {
+            #[cfg(feature = "use_csv")]
+            SourceBatchIterator::CsvBatchIterator(csv_source) => csv_source.get_column_info(), 
+            #[cfg(feature = "use_generate")]
+            SourceBatchIterator::GenerateBatchIterator(generate_source) => generate_source.get_column_info(), 
+            #[cfg(feature = "use_json")]
+            SourceBatchIterator::JsonBatchIterator(json_source) => json_source.get_column_info(), 
+            #[cfg(feature = "use_parquet")]
+            SourceBatchIterator::ParquetBatchIterator(parquet_source) => parquet_source.get_column_info(), 
+            #[cfg(feature = "use_calamine")]
+            SourceBatchIterator::SpreadSheetBatchIterator(spreadsheet_source) => spreadsheet_source.get_column_info(), 
             #[cfg(feature = "use_sqlite")]
             SourceBatchIterator::SqliteBatchIterator(sqlite_source) => sqlite_source.get_column_info(), 
+            #[cfg(feature = "use_duckdb")]
+            SourceBatchIterator::DuckdbBatchIterator(duckdb_source) => duckdb_source.get_column_info(), 
             #[cfg(feature = "use_mysql")]
             SourceBatchIterator::MysqlBatchIterator(mysql_source) => mysql_source.get_column_info(), 
             #[cfg(feature = "use_postgres")]
@@ -108,8 +239,20 @@ impl <'c, 'i>DataSourceBatchIterator for SourceBatchIterator<'i> {
     
     fn get_count(&self) -> Option<u64> {
         match self {
+            #[cfg(feature = "use_csv")]
+            SourceBatchIterator::CsvBatchIterator(csv_source) => csv_source.get_count(), 
+            #[cfg(feature = "use_generate")]
+            SourceBatchIterator::GenerateBatchIterator(generate_source) => generate_source.get_count(), 
+            #[cfg(feature = "use_json")]
+            SourceBatchIterator::JsonBatchIterator(json_source) => json_source.get_count(), 
+            #[cfg(feature = "use_parquet")]
+            SourceBatchIterator::ParquetBatchIterator(parquet_source) => parquet_source.get_count(), 
+            #[cfg(feature = "use_calamine")]
+            SourceBatchIterator::SpreadSheetBatchIterator(spreadsheet_source) => spreadsheet_source.get_count(), 
             #[cfg(feature = "use_sqlite")]
             SourceBatchIterator::SqliteBatchIterator(sqlite_source) => sqlite_source.get_count(), 
+            #[cfg(feature = "use_duckdb")]
+            SourceBatchIterator::DuckdbBatchIterator(duckdb_source) => duckdb_source.get_count(), 
             #[cfg(feature = "use_mysql")]
             SourceBatchIterator::MysqlBatchIterator(mysql_source) => mysql_source.get_count(), 
             #[cfg(feature = "use_postgres")]
@@ -120,8 +263,20 @@ impl <'c, 'i>DataSourceBatchIterator for SourceBatchIterator<'i> {
 
     fn next(&mut self) -> Option<Vec<Row>> {
         match self {
+            #[cfg(feature = "use_csv")]
+            SourceBatchIterator::CsvBatchIterator(csv_source) => csv_source.next(), 
+            #[cfg(feature = "use_generate")]
+            SourceBatchIterator::GenerateBatchIterator(generate_source) => generate_source.next(), 
+            #[cfg(feature = "use_json")]
+            SourceBatchIterator::JsonBatchIterator(json_source) => json_source.next(), 
+            #[cfg(feature = "use_parquet")]
+            SourceBatchIterator::ParquetBatchIterator(parquet_source) => parquet_source.next(), 
+            #[cfg(feature = "use_calamine")]
+            SourceBatchIterator::SpreadSheetBatchIterator(spreadsheet_source) => spreadsheet_source.next(), 
             #[cfg(feature = "use_sqlite")]
             SourceBatchIterator::SqliteBatchIterator(sqlite_source) => sqlite_source.next(), 
+            #[cfg(feature = "use_duckdb")]
+            SourceBatchIterator::DuckdbBatchIterator(duckdb_source) => duckdb_source.next(), 
             #[cfg(feature = "use_mysql")]
             SourceBatchIterator::MysqlBatchIterator(mysql_source) => mysql_source.next(), 
             #[cfg(feature = "use_postgres")]
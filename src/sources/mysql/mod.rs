@@ -1,5 +1,5 @@
-use std::fs::File;
-use std::io::Read;
+use std::fs;
+use std::io::Write;
 use std::time::Duration;
 
 use chrono;
@@ -7,53 +7,159 @@ use mysql;
 use mysql::prelude::Queryable;
 use mysql::consts::ColumnType as MyColumnType;
 use mysql::consts::ColumnFlags as MyColumnFlags;
+use openssl::{pkcs12::Pkcs12, pkey::PKey, x509::X509};
+use tempfile::TempPath;
 
 use crate::commands::common::MysqlConfigOptions;
 use crate::commands::export::MysqlSourceOptions;
 use crate::definitions::{ColumnType, Value, Row, ColumnInfo, DataSource, DataSourceConnection, DataSourceBatchIterator};
-use crate::utils::report_query_error;
+use crate::utils::{params::{bind_params, ParamValue, QueryParam}, report_query_error, sql::{split_setup, split_statements, Dialect}, tls::{read_certificate, SslMode}};
+use crate::utils::mycnf;
+use crate::utils::password::{ask_password, can_prompt, password_cache_key, remember_password, PasswordOptions};
+use crate::utils::ssh::{open_tunnel_if_enabled, SshOptions, SshTunnel, TunnelTarget};
+
+
+///mysql error code for rejected user or password
+const ER_ACCESS_DENIED_ERROR: u16 = 1045;
 
 
 pub trait GetMysqlConnectionParams {
     fn get_hostname(&self) -> &Option<String>;
     fn get_username(&self) -> &Option<String>;
-    fn get_password(&self) -> &Option<String>;
+    ///password given with --password (None inside when it should be asked for)
+    fn get_given_password(&self) -> &Option<Option<String>>;
+    fn get_password_options(&self) -> &PasswordOptions;
+    fn get_ssh_options(&self) -> &SshOptions;
     fn get_port(&self) -> &Option<u16>;
     fn get_socket(&self) -> &Option<String>;
     fn get_database(&self) -> &Option<String>;
     fn get_init(&self) -> &Vec<String>;
     fn get_timeout(&self) -> &Option<u64>;
+    fn get_sslmode(&self) -> &Option<String>;
+    fn get_ssl_ca(&self) -> &Option<String>;
+    fn get_ssl_cert(&self) -> &Option<String>;
+    fn get_ssl_key(&self) -> &Option<String>;
+
+    ///identifies password remembered by password agent
+    fn get_password_cache_key(&self) -> String {
+        password_cache_key("mysql", self.get_username(), self.get_hostname(), self.get_port(), self.get_database())
+    }
+
+    ///password, possibly obtained from environment, command or vault, or asked for.
+    ///If there is none, ~/.my.cnf is checked, as mysql client does
+    fn try_get_password(&self) -> Result<Option<String>, String> {
+        let password = self.get_password_options().resolve(self.get_given_password(), &self.get_password_cache_key())?;
+        Ok(password.or_else(|| mycnf::find_password(self.get_username())))
+    }
+
+    ///password, exiting with error message when it can't be obtained
+    fn get_password(&self) -> Option<String> {
+        match self.try_get_password() {
+            Ok(password) => password,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 }
 
 impl GetMysqlConnectionParams for MysqlSourceOptions {
     fn get_hostname(&self) -> &Option<String> { &self.host }
     fn get_username(&self) -> &Option<String> { &self.user }
-    fn get_password(&self) -> &Option<String> { &self.password }
+    fn get_given_password(&self) -> &Option<Option<String>> { &self.password }
+    fn get_password_options(&self) -> &PasswordOptions { &self.password_options }
+    fn get_ssh_options(&self) -> &SshOptions { &self.ssh_options }
     fn get_port(&self) -> &Option<u16> { &self.port }
     fn get_socket(&self) -> &Option<String> { &self.socket }
     fn get_database(&self) -> &Option<String> { &self.database }
     fn get_init(&self) -> &Vec<String> { &self.init }
     fn get_timeout(&self) -> &Option<u64> { &self.timeout }
+    fn get_sslmode(&self) -> &Option<String> { &self.sslmode }
+    fn get_ssl_ca(&self) -> &Option<String> { &self.ssl_ca }
+    fn get_ssl_cert(&self) -> &Option<String> { &self.ssl_cert }
+    fn get_ssl_key(&self) -> &Option<String> { &self.ssl_key }
 }
 
 impl GetMysqlConnectionParams for MysqlConfigOptions {
     fn get_hostname(&self) -> &Option<String> { &self.host }
     fn get_username(&self) -> &Option<String> { &self.user }
-    fn get_password(&self) -> &Option<String> { &self.password }
+    fn get_given_password(&self) -> &Option<Option<String>> { &self.password }
+    fn get_password_options(&self) -> &PasswordOptions { &self.password_options }
+    fn get_ssh_options(&self) -> &SshOptions { &self.ssh_options }
     fn get_port(&self) -> &Option<u16> { &self.port }
     fn get_socket(&self) -> &Option<String> { &self.socket }
     fn get_database(&self) -> &Option<String> { &self.database }
     fn get_init(&self) -> &Vec<String> { &self.init }
     fn get_timeout(&self) -> &Option<u64> { &self.timeout }
+    fn get_sslmode(&self) -> &Option<String> { &self.sslmode }
+    fn get_ssl_ca(&self) -> &Option<String> { &self.ssl_ca }
+    fn get_ssl_cert(&self) -> &Option<String> { &self.ssl_cert }
+    fn get_ssl_key(&self) -> &Option<String> { &self.ssl_key }
+}
+
+///write new file with random name, readable only by current user, to temp dir. Used for certificates mysql
+///reads from path, file is removed when returned path is dropped
+fn write_temp_file(suffix: &str, data: &[u8]) -> Result<TempPath, String> {
+    let mut file = tempfile::Builder::new()
+        .prefix("dbfish-")
+        .suffix(suffix)
+        .tempfile()
+        .map_err(|e| format!("can't create temporary file: {}", e))?;
+    file.write_all(data).map_err(|e| format!("can't write {}: {}", file.path().display(), e))?;
+    Ok(file.into_temp_path())
+}
+
+///mysql takes CA certificate as DER and client certificate as PKCS#12 archive, so PEM files are converted
+///to temporary files. Returns ssl options and files to remove after connecting.
+fn mysql_ssl_opts(sslmode: SslMode, mysql_options: &dyn GetMysqlConnectionParams) -> Result<(mysql::SslOpts, Vec<TempPath>), String> {
+    let mut temp_files = vec![];
+    let mut ssl_opts = mysql::SslOpts::default()
+        .with_danger_accept_invalid_certs(sslmode.accept_invalid_certs(mysql_options.get_ssl_ca().is_some()))
+        .with_danger_skip_domain_validation(sslmode.accept_invalid_hostnames());
+    if let Some(ssl_ca) = mysql_options.get_ssl_ca() {
+        let der = read_certificate(ssl_ca)?.to_der().map_err(|e| format!("invalid CA certificate {}: {}", ssl_ca, e))?;
+        let path = write_temp_file(".der", &der)?;
+        ssl_opts = ssl_opts.with_root_cert_path(Some(path.to_path_buf()));
+        temp_files.push(path);
+    }
+    match (mysql_options.get_ssl_cert(), mysql_options.get_ssl_key()) {
+        (Some(ssl_cert), Some(ssl_key)) => {
+            let cert = fs::read(ssl_cert).map_err(|e| format!("can't read client certificate {}: {}", ssl_cert, e))?;
+            let key = fs::read(ssl_key).map_err(|e| format!("can't read client key {}: {}", ssl_key, e))?;
+            let cert = X509::from_pem(&cert).map_err(|e| format!("invalid client certificate {}: {}", ssl_cert, e))?;
+            let key = PKey::private_key_from_pem(&key).map_err(|e| format!("invalid client key {}: {}", ssl_key, e))?;
+            let der = Pkcs12::builder()
+                .name("dbfish")
+                .pkey(&key)
+                .cert(&cert)
+                .build2("")
+                .and_then(|pkcs12| pkcs12.to_der())
+                .map_err(|e| format!("can't use client certificate: {}", e))?;
+            let path = write_temp_file(".p12", &der)?;
+            ssl_opts = ssl_opts.with_pkcs12_path(Some(path.to_path_buf()));
+            temp_files.push(path);
+        },
+        (None, None) => {},
+        _ => return Err("--ssl-cert and --ssl-key must be given together".to_string()),
+    }
+    Ok((ssl_opts, temp_files))
 }
 
-pub fn establish_mysql_connection(mysql_options: &dyn GetMysqlConnectionParams ) -> mysql::PooledConn {
+///database address as seen from ssh host
+pub fn mysql_tunnel_target(mysql_options: &dyn GetMysqlConnectionParams) -> TunnelTarget {
+    match mysql_options.get_socket() {
+        Some(socket) => TunnelTarget::Socket(socket.clone()),
+        None => TunnelTarget::Tcp(mysql_options.get_hostname().clone().unwrap_or_else(|| "localhost".to_string()), mysql_options.get_port().unwrap_or(3306)),
+    }
+}
 
+fn connect(mysql_options: &dyn GetMysqlConnectionParams, tunnel: &Option<SshTunnel>, password: &Option<String>, sslmode: SslMode, ssl_opts: &Option<mysql::SslOpts>) -> Result<mysql::PooledConn, mysql::Error> {
 
     let mut option_builder = mysql::OptsBuilder::new()
         .db_name(mysql_options.get_database().to_owned())
         .user(mysql_options.get_username().to_owned())
-        .pass(mysql_options.get_password().to_owned());
+        .pass(password.to_owned());
 
     if let Some(timeout) = mysql_options.get_timeout() {
          option_builder = option_builder
@@ -62,7 +168,11 @@ pub fn establish_mysql_connection(mysql_options: &dyn GetMysqlConnectionParams )
             .tcp_connect_timeout(Some(Duration::from_secs(*timeout)));
     };
 
-    if let Some(ref socket) = mysql_options.get_socket() {
+    if let Some(tunnel) = tunnel {
+        option_builder = option_builder
+            .ip_or_hostname(Some("127.0.0.1"))
+            .tcp_port(tunnel.local_port);
+    } else if let Some(ref socket) = mysql_options.get_socket() {
         option_builder = option_builder.socket(Some(socket.to_owned()));
     } else {
         option_builder = option_builder
@@ -74,7 +184,83 @@ pub fn establish_mysql_connection(mysql_options: &dyn GetMysqlConnectionParams )
         option_builder = option_builder.init(mysql_options.get_init().to_owned());
     };
 
-    mysql::Pool::new(option_builder).unwrap().get_conn().unwrap()
+    let ssl_opts = match ssl_opts {
+        Some(ssl_opts) => ssl_opts.clone(),
+        None => return mysql::Pool::new(option_builder).and_then(|pool| pool.get_conn()),
+    };
+    let opts = mysql::Opts::from(option_builder);
+    let connected = mysql::Pool::new(mysql::OptsBuilder::from_opts(opts.clone()).ssl_opts(ssl_opts)).and_then(|pool| pool.get_conn());
+    match connected {
+        Err(mysql::Error::DriverError(mysql::DriverError::TlsNotSupported)) if sslmode == SslMode::Prefer => {
+            mysql::Pool::new(opts).and_then(|pool| pool.get_conn())
+        },
+        connected => connected,
+    }
+}
+
+///access denied, which is also reported when password is missing
+fn is_auth_error(error: &mysql::Error) -> bool {
+    matches!(error, mysql::Error::MySqlError(e) if e.code == ER_ACCESS_DENIED_ERROR)
+}
+
+///connect to database, through ssh tunnel if ssh host is given. Tunnel and temporary certificate files
+///have to be kept while connection is used, as pool can open new connections with them.
+///With interactive set, password is asked for when it's missing or rejected
+pub fn try_establish_mysql_connection(mysql_options: &dyn GetMysqlConnectionParams, interactive: bool) -> Result<(mysql::PooledConn, Option<SshTunnel>, Vec<TempPath>), String> {
+
+    let cache_key = mysql_options.get_password_cache_key();
+    let sslmode = SslMode::parse(mysql_options.get_sslmode())?;
+    //mysql checks certificate against host it connects to, which is 127.0.0.1 for ssh tunnel
+    if sslmode == SslMode::VerifyFull && mysql_options.get_ssh_options().is_enabled() {
+        return Err("--sslmode verify-full can't check mysql server host name through ssh tunnel, use verify-ca".to_string());
+    }
+    let (ssl_opts, temp_files) = match sslmode {
+        SslMode::Disable => (None, vec![]),
+        sslmode => {
+            let (ssl_opts, temp_files) = mysql_ssl_opts(sslmode, mysql_options)?;
+            (Some(ssl_opts), temp_files)
+        },
+    };
+    let (connection, tunnel) = connect_with_password(mysql_options, &cache_key, sslmode, &ssl_opts, interactive)?;
+    Ok((connection, tunnel, temp_files))
+}
+
+fn connect_with_password(mysql_options: &dyn GetMysqlConnectionParams, cache_key: &str, sslmode: SslMode, ssl_opts: &Option<mysql::SslOpts>, interactive: bool) -> Result<(mysql::PooledConn, Option<SshTunnel>), String> {
+    let mut password = mysql_options.try_get_password()?;
+    let tunnel = open_tunnel_if_enabled(mysql_options.get_ssh_options(), mysql_tunnel_target(mysql_options), mysql_options.get_timeout())?;
+    let mut asked = mysql_options.get_given_password() == &Some(None);
+    let mut attempts = 0;
+    //on authentication failure password is asked for, up to 3 times
+    loop {
+        match connect(mysql_options, &tunnel, &password, sslmode, ssl_opts) {
+            Ok(connection) => {
+                if asked {
+                    remember_password(cache_key, password.as_deref().unwrap_or(""));
+                }
+                return Ok((connection, tunnel));
+            },
+            Err(e) if is_auth_error(&e) && attempts < 3 && interactive && can_prompt() => {
+                if password.is_some() {
+                    eprintln!("Error: can't connect to mysql: {}", e);
+                }
+                password = Some(ask_password(cache_key, attempts == 0 && password.is_none())?);
+                asked = true;
+                attempts += 1;
+            },
+            Err(e) => return Err(format!("can't connect to mysql: {}", e)),
+        }
+    }
+}
+
+///connect to database, exiting with error message if that fails
+pub fn establish_mysql_connection(mysql_options: &dyn GetMysqlConnectionParams) -> (mysql::PooledConn, Option<SshTunnel>, Vec<TempPath>) {
+    match try_establish_mysql_connection(mysql_options, true) {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 
@@ -92,16 +278,36 @@ impl MysqlSource {
 pub struct MysqlSourceConnection<'c> {
     connection: mysql::PooledConn,
     source: &'c MysqlSource,
+    //kept open while connection is used
+    _tunnel: Option<SshTunnel>,
+    //certificate files used by ssl options, removed when dropped
+    _temp_files: Vec<TempPath>,
+}
+
+//queries with parameters are executed as prepared statements, which use binary protocol
+pub enum MysqlResults<'i> {
+    Text(mysql::QueryResult<'i, 'i, 'i, mysql::Text>),
+    Binary(mysql::QueryResult<'i, 'i, 'i, mysql::Binary>),
 }
 
 pub struct MysqlSourceBatchIterator<'i> {
     batch_size: u64,
     //connection: &'i mysql::PooledConn,
     count: Option<u64>,
-    results: mysql::QueryResult<'i, 'i, 'i, mysql::Text>,
+    results: MysqlResults<'i>,
     //source_connection: &'i mut MysqlSourceConnection<'c>
 }
 
+pub fn mysql_param_value(param: &QueryParam) -> mysql::Value {
+    match &param.value {
+        ParamValue::Int(value) => mysql::Value::Int(*value),
+        ParamValue::Float(value) => mysql::Value::Float(*value),
+        ParamValue::Bool(value) => mysql::Value::Int(*value as i64),
+        ParamValue::String(value) => mysql::Value::Bytes(value.as_bytes().to_vec()),
+        ParamValue::Null => mysql::Value::NULL,
+    }
+}
+
 impl <'c, 'i>MysqlSourceBatchIterator<'i> {
 
     pub fn mysql_to_row(column_info: &[ColumnInfo], mysql_row: mysql::Row) -> Row {
@@ -117,28 +323,27 @@ impl <'c, 'i>MysqlSourceBatchIterator<'i> {
                     Err(e) => panic!(format!("mysq: invalid utf8 in '{:?}' for row: {:?} ({})", v, value, e))
                 },
                 mysql::Value::Date(year, month, day, hour, minute, second, _microsecond) => {
-                    match column_info[idx].data_type {
-                        ColumnType::Date => result.push(
-                            Value::Date(chrono::NaiveDate::from_ymd(i32::from(*year), u32::from(*month), u32::from(*day)))
-                        ),
-                        ColumnType::DateTime => result.push(
-                            Value::DateTime(chrono::NaiveDate::from_ymd(i32::from(*year), u32::from(*month), u32::from(*day)).and_hms(u32::from(*hour), u32::from(*minute), u32::from(*second)))
-                        ),
-                        ColumnType::Time => result.push(
-                            Value::Time(chrono::NaiveTime::from_hms(u32::from(*hour), u32::from(*minute), u32::from(*second)))
-                        ),
-                        ColumnType::Timestamp => result.push(
-                            Value::DateTime(chrono::NaiveDate::from_ymd(i32::from(*year), u32::from(*month), u32::from(*day)).and_hms(u32::from(*hour), u32::from(*minute), u32::from(*second)))
+                    let date = chrono::NaiveDate::from_ymd_opt(i32::from(*year), u32::from(*month), u32::from(*day));
+                    let time = chrono::NaiveTime::from_hms_opt(u32::from(*hour), u32::from(*minute), u32::from(*second));
+                    match (&column_info[idx].data_type, date, time) {
+                        (ColumnType::Date, Some(date), _) => result.push(Value::Date(date)),
+                        (ColumnType::DateTime | ColumnType::Timestamp, Some(date), Some(time)) => result.push(Value::DateTime(date.and_time(time))),
+                        (ColumnType::Time, _, Some(time)) => result.push(Value::Time(time)),
+                        //zero dates (0000-00-00) are exported as text, the way text protocol returns them
+                        (ColumnType::Date, None, _) => result.push(Value::String(format!("{:04}-{:02}-{:02}", year, month, day))),
+                        (ColumnType::DateTime | ColumnType::Timestamp, _, _) => result.push(
+                            Value::String(format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, hour, minute, second))
                         ),
                         _ => panic!("mysql: unsupported conversion: {:?} => {:?}", value, column_info[idx])
                     }
                 },
-                //TODO: what to do with negative?
-                mysql::Value::Time(_negative, _day, hour, minute, second, _microsecond) => {
-                    match column_info[idx].data_type {
-                        ColumnType::Time => result.push(
-                            Value::Time(chrono::NaiveTime::from_hms(u32::from(*hour), u32::from(*minute), u32::from(*second)))
-                        ),
+                mysql::Value::Time(negative, days, hour, minute, second, _microsecond) => {
+                    match (&column_info[idx].data_type, chrono::NaiveTime::from_hms_opt(u32::from(*hour), u32::from(*minute), u32::from(*second))) {
+                        (ColumnType::Time, Some(time)) if !*negative && *days == 0 => result.push(Value::Time(time)),
+                        //negative times and ones longer than a day are exported as text, the way text protocol returns them
+                        (ColumnType::Time, _) => result.push(Value::String(
+                            format!("{}{:02}:{:02}:{:02}", if *negative { "-" } else { "" }, days * 24 + u32::from(*hour), minute, second)
+                        )),
                         _ => panic!("mysql: unsupported conversion: {:?} => {:?}", value, column_info[idx])
                     }
                 },
@@ -156,11 +361,13 @@ where 'c: 'i,
     fn connect(&'c self) -> MysqlSourceConnection
     {
 
-        let connection = establish_mysql_connection(&self.options);
+        let (connection, tunnel, temp_files) = establish_mysql_connection(&self.options);
 
         MysqlSourceConnection {
             connection,
             source: &self,
+            _tunnel: tunnel,
+            _temp_files: temp_files,
         }
     }
 
@@ -170,30 +377,63 @@ where 'c: 'i,
 
 }
 
+impl <'c> MysqlSourceConnection<'c> {
+
+    ///replace :name placeholders, returning query and values to bind
+    fn bind_query(&self, query: &str) -> (String, Vec<mysql::Value>) {
+        match bind_params(query, &self.source.options.params, Dialect::Mysql, false, |_, _| "?".to_string()) {
+            Ok((query, bound_params)) => (query, bound_params.iter().map(|param| mysql_param_value(param)).collect()),
+            Err(e) => {
+                report_query_error(query, &e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
 impl <'c, 'i>DataSourceConnection<'i, MysqlSourceBatchIterator<'i>> for MysqlSourceConnection<'c>
 {
-    fn batch_iterator(&'i mut self, batch_size: u64) -> MysqlSourceBatchIterator<'i>
-    {
-        let query = match &self.source.options.query {
-            Some(q) => q.to_owned(),
-            None => match &self.source.options.query_file {
-                Some(path_buf) => {
-                    let mut sql = String::new();
-                    File::open(path_buf).unwrap().read_to_string(&mut sql).unwrap();
-                    sql
-                },
-                None => panic!("You need to pass either q or query-file option"),
+    fn execute(&mut self, sql: &str) {
+        for statement in split_statements(sql, Dialect::Mysql) {
+            let (statement, values) = self.bind_query(&statement);
+            let result = if self.source.options.params.is_empty() {
+                self.connection.query_drop(&statement)
+            } else {
+                self.connection.exec_drop(&statement, values)
+            };
+            if let Err(e) = result {
+                report_query_error(&statement, &format!("{:?}", e));
+                std::process::exit(1);
             }
-        };
-        
+        }
+    }
+
+    fn batch_iterator(&'i mut self, query: &str, batch_size: u64) -> MysqlSourceBatchIterator<'i>
+    {
+        //all statements but last one prepare data for it
+        let (setup, query) = split_setup(query, Dialect::Mysql);
+        for statement in setup.iter() {
+            self.execute(statement);
+        }
+        let params = &self.source.options.params;
+        let (query, values) = self.bind_query(&query);
         let count: Option<u64> = {if self.source.options.count {
             let count_query = format!("select count(*) from ({}) q", query);
-            let count_value: u64 = self.connection.query_first(count_query).unwrap().unwrap();
+            let count_value: u64 = if params.is_empty() {
+                self.connection.query_first(count_query).unwrap().unwrap()
+            } else {
+                self.connection.exec_first(count_query, values.clone()).unwrap().unwrap()
+            };
             Some(count_value)
         } else {
             None
         }};
-        let mysql_result = { match self.connection.query_iter(query.as_str()) {
+        let mysql_result = if params.is_empty() {
+            self.connection.query_iter(&query).map(MysqlResults::Text)
+        } else {
+            self.connection.exec_iter(&query, values).map(MysqlResults::Binary)
+        };
+        let mysql_result = { match mysql_result {
             Ok(v) => v,
             Err(e) => {
                 report_query_error(&query, &format!("{:?}", e));
@@ -216,7 +456,11 @@ impl <'c, 'i>DataSourceBatchIterator for MysqlSourceBatchIterator<'i>
 {
     fn get_column_info(&self) -> Vec<ColumnInfo> {
         let mut result = vec![];
-        for column in  self.results.columns().as_ref() {
+        let columns = match &self.results {
+            MysqlResults::Text(results) => results.columns(),
+            MysqlResults::Binary(results) => results.columns(),
+        };
+        for column in columns.as_ref() {
             let column_type = column.column_type();
             let flags = column.flags();
             result.push(ColumnInfo {
@@ -279,11 +523,19 @@ impl <'c, 'i>DataSourceBatchIterator for MysqlSourceBatchIterator<'i>
     {
  
         let ci = self.get_column_info();
-        let results: Vec<Row> =  self.results
-            .by_ref()
-            .take(self.batch_size as usize)
-            .map(|v|{ MysqlSourceBatchIterator::mysql_to_row(&ci, v.unwrap())})
-            .collect();
+        let batch_size = self.batch_size as usize;
+        let results: Vec<Row> = match &mut self.results {
+            MysqlResults::Text(results) => results
+                .by_ref()
+                .take(batch_size)
+                .map(|v|{ MysqlSourceBatchIterator::mysql_to_row(&ci, v.unwrap())})
+                .collect(),
+            MysqlResults::Binary(results) => results
+                .by_ref()
+                .take(batch_size)
+                .map(|v|{ MysqlSourceBatchIterator::mysql_to_row(&ci, v.unwrap())})
+                .collect(),
+        };
         match results.len() {
             0 => None,
             _ => Some(results)
@@ -0,0 +1,390 @@
+use std::fs::File;
+use std::sync::Arc;
+
+use chrono::{self, TimeZone};
+use parquet::basic::{ConvertedType, LogicalType, TimeUnit, Type as PhysicalType};
+use parquet::errors::ParquetError;
+use parquet::file::footer::parse_metadata;
+use parquet::file::metadata::{FileMetaData, ParquetMetaData, RowGroupMetaData};
+use parquet::file::properties::{ReaderProperties, ReaderPropertiesPtr};
+use parquet::file::reader::{FileReader, RowGroupReader};
+use parquet::file::serialized_reader::SerializedRowGroupReader;
+use parquet::record::{Field, reader::RowIter};
+use parquet::schema::types::{SchemaDescriptor, Type};
+
+use crate::commands::export::ParquetSourceOptions;
+use crate::definitions::{ColumnType, Value, Row, ColumnInfo, DataSource, DataSourceConnection, DataSourceBatchIterator};
+
+
+///map type of top level parquet field to column type, None if it can't be read
+pub fn parquet_column_type(field: &Type) -> Option<ColumnType> {
+    if field.is_group() {
+        return Some(ColumnType::JSON);
+    }
+    let info = field.get_basic_info();
+    let column_type = match (field.get_physical_type(), info.converted_type(), info.logical_type()) {
+        (PhysicalType::BOOLEAN, _, _) => ColumnType::Bool,
+        (PhysicalType::INT32, ConvertedType::INT_8, _) => ColumnType::I8,
+        (PhysicalType::INT32, ConvertedType::INT_16, _) => ColumnType::I16,
+        (PhysicalType::INT32, ConvertedType::INT_32, _) | (PhysicalType::INT32, ConvertedType::NONE, _) => ColumnType::I32,
+        (PhysicalType::INT32, ConvertedType::UINT_8, _) => ColumnType::U8,
+        (PhysicalType::INT32, ConvertedType::UINT_16, _) => ColumnType::U16,
+        (PhysicalType::INT32, ConvertedType::UINT_32, _) => ColumnType::U32,
+        (PhysicalType::INT32, ConvertedType::DATE, _) => ColumnType::Date,
+        (PhysicalType::INT32, ConvertedType::TIME_MILLIS, _) => ColumnType::Time,
+        (PhysicalType::INT64, ConvertedType::NONE, Some(LogicalType::Timestamp { .. })) => ColumnType::DateTime,
+        (PhysicalType::INT64, ConvertedType::NONE, Some(LogicalType::Time { .. })) => ColumnType::Time,
+        (PhysicalType::INT64, ConvertedType::INT_64, _) | (PhysicalType::INT64, ConvertedType::NONE, _) => ColumnType::I64,
+        (PhysicalType::INT64, ConvertedType::UINT_64, _) => ColumnType::U64,
+        (PhysicalType::INT64, ConvertedType::TIME_MICROS, _) => ColumnType::Time,
+        (PhysicalType::INT64, ConvertedType::TIMESTAMP_MILLIS, _) | (PhysicalType::INT64, ConvertedType::TIMESTAMP_MICROS, _) => ColumnType::DateTime,
+        (PhysicalType::INT96, _, _) => ColumnType::DateTime,
+        (PhysicalType::FLOAT, _, _) => ColumnType::F32,
+        (PhysicalType::DOUBLE, _, _) => ColumnType::F64,
+        (PhysicalType::BYTE_ARRAY, ConvertedType::UTF8, _) | (PhysicalType::BYTE_ARRAY, ConvertedType::ENUM, _) => ColumnType::String,
+        (PhysicalType::BYTE_ARRAY, ConvertedType::JSON, _) => ColumnType::JSON,
+        (PhysicalType::BYTE_ARRAY, ConvertedType::BSON, _) | (PhysicalType::BYTE_ARRAY, ConvertedType::NONE, _) => ColumnType::Bytes,
+        (PhysicalType::FIXED_LEN_BYTE_ARRAY, ConvertedType::NONE, _) => ColumnType::Bytes,
+        (_, ConvertedType::DECIMAL, _) => ColumnType::Decimal,
+        _ => return None,
+    };
+    Some(column_type)
+}
+
+///nanoseconds in unit of time columns and int64 timestamp columns without converted type
+fn nanoseconds_per_unit(field: &Type) -> i64 {
+    let info = field.get_basic_info();
+    match (info.converted_type(), info.logical_type()) {
+        (ConvertedType::TIME_MILLIS, _)
+        | (_, Some(LogicalType::Timestamp { unit: TimeUnit::MILLIS(_), .. }))
+        | (_, Some(LogicalType::Time { unit: TimeUnit::MILLIS(_), .. })) => 1_000_000,
+        (ConvertedType::TIME_MICROS, _)
+        | (_, Some(LogicalType::Timestamp { unit: TimeUnit::MICROS(_), .. }))
+        | (_, Some(LogicalType::Time { unit: TimeUnit::MICROS(_), .. })) => 1_000,
+        _ => 1,
+    }
+}
+
+///record reader can't decode time columns with converted type, so they are read as plain integers
+fn readable_type(field: &Arc<Type>) -> Arc<Type> {
+    let info = field.get_basic_info();
+    if field.is_group() {
+        let mut builder = Type::group_type_builder(field.name())
+            .with_fields(field.get_fields().iter().map(readable_type).collect())
+            .with_converted_type(info.converted_type())
+            .with_logical_type(info.logical_type())
+            .with_id(if info.has_id() { Some(info.id()) } else { None });
+        if info.has_repetition() {
+            builder = builder.with_repetition(info.repetition());
+        }
+        return Arc::new(builder.build().unwrap());
+    }
+    match info.converted_type() {
+        ConvertedType::TIME_MILLIS | ConvertedType::TIME_MICROS => Arc::new(
+            Type::primitive_type_builder(field.name(), field.get_physical_type())
+                .with_repetition(info.repetition())
+                .build()
+                .unwrap()
+        ),
+        _ => field.clone(),
+    }
+}
+
+///file reader with schema changed by readable_type. Column chunks are decoded using types from row group metadata,
+///so it is rebuilt with that schema too
+struct ReadableFileReader {
+    file: Arc<File>,
+    metadata: ParquetMetaData,
+    properties: ReaderPropertiesPtr,
+}
+
+impl ReadableFileReader {
+    fn new(file: File, metadata: &ParquetMetaData) -> Result<ReadableFileReader, ParquetError> {
+        let file_metadata = metadata.file_metadata();
+        let schema = Arc::new(SchemaDescriptor::new(readable_type(&file_metadata.schema_descr().root_schema_ptr())));
+        let row_groups = metadata.row_groups()
+            .iter()
+            .map(|row_group| RowGroupMetaData::from_thrift(schema.clone(), row_group.to_thrift()))
+            .collect::<Result<Vec<RowGroupMetaData>, ParquetError>>()?;
+        let file_metadata = FileMetaData::new(
+            file_metadata.version(),
+            file_metadata.num_rows(),
+            file_metadata.created_by().map(|created_by| created_by.to_string()),
+            file_metadata.key_value_metadata().cloned(),
+            schema,
+            file_metadata.column_orders().cloned(),
+        );
+        Ok(ReadableFileReader {
+            file: Arc::new(file),
+            metadata: ParquetMetaData::new(file_metadata, row_groups),
+            properties: Arc::new(ReaderProperties::builder().build()),
+        })
+    }
+}
+
+impl FileReader for ReadableFileReader {
+    fn metadata(&self) -> &ParquetMetaData {
+        &self.metadata
+    }
+
+    fn num_row_groups(&self) -> usize {
+        self.metadata.num_row_groups()
+    }
+
+    fn get_row_group(&self, i: usize) -> Result<Box<dyn RowGroupReader + '_>, ParquetError> {
+        Ok(Box::new(SerializedRowGroupReader::new(self.file.clone(), self.metadata.row_group(i), None, self.properties.clone())?))
+    }
+
+    fn get_row_iter(&self, projection: Option<Type>) -> Result<RowIter<'_>, ParquetError> {
+        RowIter::from_file(projection, self)
+    }
+}
+
+fn time_from_nanoseconds(nanoseconds: i64) -> Option<chrono::NaiveTime> {
+    chrono::NaiveTime::from_num_seconds_from_midnight_opt((nanoseconds / 1_000_000_000) as u32, (nanoseconds % 1_000_000_000) as u32)
+}
+
+fn datetime_from_nanoseconds(value: i64) -> Value {
+    match chrono::Utc.timestamp_opt(value.div_euclid(1_000_000_000), value.rem_euclid(1_000_000_000) as u32).single() {
+        Some(datetime) => Value::DateTime(datetime.naive_utc()),
+        None => Value::I64(value),
+    }
+}
+
+pub fn field_to_value(field: Field, column_type: &ColumnType, nanoseconds_per_unit: i64) -> Value {
+    match field {
+        Field::Null => Value::None,
+        Field::Bool(value) => Value::Bool(value),
+        Field::Byte(value) => Value::I8(value),
+        Field::Short(value) => Value::I16(value),
+        Field::Int(value) => match column_type {
+            ColumnType::Time => match time_from_nanoseconds(i64::from(value).saturating_mul(nanoseconds_per_unit)) {
+                Some(time) => Value::Time(time),
+                None => Value::I32(value),
+            },
+            _ => Value::I32(value),
+        },
+        Field::Long(value) => match column_type {
+            ColumnType::DateTime => datetime_from_nanoseconds(value.saturating_mul(nanoseconds_per_unit)),
+            ColumnType::Time => match time_from_nanoseconds(value.saturating_mul(nanoseconds_per_unit)) {
+                Some(time) => Value::Time(time),
+                None => Value::I64(value),
+            },
+            _ => Value::I64(value),
+        },
+        Field::UByte(value) => Value::U8(value),
+        Field::UShort(value) => Value::U16(value),
+        Field::UInt(value) => Value::U32(value),
+        Field::ULong(value) => Value::U64(value),
+        Field::Float(value) => Value::F32(value),
+        Field::Double(value) => Value::F64(value),
+        Field::Decimal(_) => Value::String(field.to_string()),
+        Field::Str(value) => match column_type {
+            ColumnType::JSON => Value::JSON(value),
+            _ => Value::String(value),
+        },
+        Field::Bytes(value) => Value::Bytes(value.data().to_vec()),
+        Field::Date(days) => match chrono::NaiveDate::from_num_days_from_ce_opt(days + 719_163) {
+            Some(date) => Value::Date(date),
+            None => Value::I32(days),
+        },
+        Field::TimestampMillis(value) => datetime_from_nanoseconds(value.saturating_mul(1_000_000)),
+        Field::TimestampMicros(value) => datetime_from_nanoseconds(value.saturating_mul(1_000)),
+        Field::Group(_) | Field::ListInternal(_) | Field::MapInternal(_) => Value::JSON(field.to_json_value().to_string()),
+    }
+}
+
+
+pub struct ParquetSource {
+    options: ParquetSourceOptions,
+}
+
+pub struct ParquetSourceConnection<'c> {
+    source: &'c ParquetSource,
+}
+
+pub struct ParquetSourceBatchIterator<'i> {
+    batch_size: u64,
+    columns: Vec<ColumnInfo>,
+    count: u64,
+    nanoseconds_per_unit: Vec<i64>,
+    rows: RowIter<'i>,
+    source: &'i ParquetSource,
+}
+
+impl ParquetSource {
+    pub fn init(parquet_options: &ParquetSourceOptions) -> ParquetSource {
+        ParquetSource { options: parquet_options.to_owned() }
+    }
+}
+
+
+impl <'c, 'i> DataSource<'c, 'i, ParquetSourceConnection<'c>, ParquetSourceBatchIterator<'i>> for ParquetSource
+where 'c: 'i,
+{
+    fn connect(&'c self) -> ParquetSourceConnection<'c>
+    {
+        ParquetSourceConnection {
+            source: &self,
+        }
+    }
+
+    fn get_type_name(&self) -> String {"parquet".to_string()}
+    fn get_name(&self) -> String { "parquet".to_string() }
+}
+
+impl <'c, 'i>DataSourceConnection<'i, ParquetSourceBatchIterator<'i>> for ParquetSourceConnection<'c>
+where 'c: 'i,
+{
+    ///parquet source has no query language, whole file is returned
+    fn batch_iterator(&'i mut self, _query: &str, batch_size: u64) -> ParquetSourceBatchIterator<'i>
+    {
+        let options = &self.source.options;
+        let (file, metadata) = match File::open(&options.filename)
+            .map_err(|e| e.to_string())
+            .and_then(|file| parse_metadata(&file).map(|metadata| (file, metadata)).map_err(|e| e.to_string())) {
+            Ok(opened) => opened,
+            Err(e) => {
+                eprintln!("Error: could not open {}: {}", options.filename, e);
+                std::process::exit(1);
+            }
+        };
+        let reader = match ReadableFileReader::new(file, &metadata) {
+            Ok(reader) => reader,
+            Err(e) => {
+                eprintln!("Error: could not read {}: {}", options.filename, e);
+                std::process::exit(1);
+            }
+        };
+        let metadata = metadata.file_metadata();
+        let count = metadata.num_rows() as u64;
+        let fields: Vec<Arc<Type>> = metadata.schema().get_fields().to_vec();
+        let selected_fields: Vec<Arc<Type>> = if options.columns.is_empty() {
+            fields
+        } else {
+            options.columns
+                .iter()
+                .map(|name| match fields.iter().find(|field| field.name() == name) {
+                    Some(field) => field.clone(),
+                    None => {
+                        eprintln!(
+                            "Error: column {} not found in {}. Available columns: {}",
+                            name,
+                            options.filename,
+                            fields.iter().map(|field| field.name()).collect::<Vec<&str>>().join(", ")
+                        );
+                        std::process::exit(1);
+                    }
+                })
+                .collect()
+        };
+        let columns = selected_fields
+            .iter()
+            .map(|field| match parquet_column_type(field) {
+                Some(data_type) => ColumnInfo { name: field.name().to_string(), data_type },
+                None => {
+                    eprintln!("Error: type of column {} is not supported, use --columns to skip it", field.name());
+                    std::process::exit(1);
+                }
+            })
+            .collect();
+        let nanoseconds_per_unit = selected_fields.iter().map(|field| nanoseconds_per_unit(field)).collect();
+        //only selected columns are decoded
+        let projection = match Type::group_type_builder("schema").with_fields(selected_fields.iter().map(readable_type).collect()).build() {
+            Ok(projection) => projection,
+            Err(e) => {
+                eprintln!("Error: invalid column selection: {}", e);
+                std::process::exit(1);
+            }
+        };
+        let rows = match RowIter::from_file_into(Box::new(reader)).project(Some(projection)) {
+            Ok(rows) => rows,
+            Err(e) => {
+                eprintln!("Error: could not read {}: {}", options.filename, e);
+                std::process::exit(1);
+            }
+        };
+
+        ParquetSourceBatchIterator {
+            batch_size,
+            columns,
+            count,
+            nanoseconds_per_unit,
+            rows,
+            source: self.source,
+        }
+    }
+}
+
+
+impl <'i>DataSourceBatchIterator for ParquetSourceBatchIterator<'i>
+{
+    fn get_column_info(&self) -> Vec<ColumnInfo> {
+        self.columns.clone()
+    }
+
+    fn get_count(&self) -> Option<u64> {
+        Some(self.count)
+    }
+
+    ///row groups are decoded one at a time, as rows are requested
+    fn next(&mut self) -> Option<Vec<Row>>
+    {
+        let mut rows = vec![];
+        while rows.len() < self.batch_size as usize {
+            match self.rows.next() {
+                Some(Ok(row)) => rows.push(
+                    row.get_column_iter()
+                        .zip(self.columns.iter().zip(self.nanoseconds_per_unit.iter()))
+                        .map(|((_name, field), (column, nanoseconds_per_unit))| field_to_value(field.clone(), &column.data_type, *nanoseconds_per_unit))
+                        .collect()
+                ),
+                Some(Err(e)) => {
+                    eprintln!("Error: could not read {}: {}", self.source.options.filename, e);
+                    std::process::exit(1);
+                },
+                None => break,
+            }
+        }
+        if !rows.is_empty() {
+            Some(rows)
+        } else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use parquet::schema::parser::parse_message_type;
+
+    use super::parquet_column_type;
+    use crate::definitions::ColumnType;
+
+    #[test]
+    fn test_parquet_column_type() {
+        let schema = parse_message_type("
+            message schema {
+                REQUIRED INT32 a (INT_16);
+                OPTIONAL BYTE_ARRAY b (UTF8);
+                OPTIONAL INT64 c (TIMESTAMP(NANOS,true));
+                OPTIONAL FIXED_LEN_BYTE_ARRAY (16) d (DECIMAL(30,2));
+                OPTIONAL group e (LIST) { repeated group list { optional int32 element; } }
+                OPTIONAL INT32 f (TIME_MILLIS);
+                OPTIONAL INT64 g (TIME_MICROS);
+            }").unwrap();
+        let types: Vec<Option<ColumnType>> = schema.get_fields().iter().map(|field| parquet_column_type(field)).collect();
+        assert_eq!(types, vec![
+            Some(ColumnType::I16),
+            Some(ColumnType::String),
+            Some(ColumnType::DateTime),
+            Some(ColumnType::Decimal),
+            Some(ColumnType::JSON),
+            Some(ColumnType::Time),
+            Some(ColumnType::Time),
+        ]);
+    }
+
+}
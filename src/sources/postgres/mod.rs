@@ -1,60 +1,115 @@
-use std::fs::File;
-use std::io::Read;
+use std::collections::HashMap;
+use std::time::Duration;
 
 use fallible_iterator::FallibleIterator;
-use postgres::{self, Client, NoTls, types::Kind};
+use postgres::{self, Client, NoTls, config::SslMode as PgSslMode, error::{DbError, SqlState}, tls::MakeTlsConnect, types::{Kind, ToSql}};
+use native_tls::TlsConnector;
+use postgres_native_tls::MakeTlsConnector;
 use urlencoding;
 
 use crate::commands::common::PostgresConfigOptions;
 use crate::commands::export::PostgresSourceOptions;
 use crate::definitions::{ColumnType, Value, Row, ColumnInfo, DataSource, DataSourceConnection, DataSourceBatchIterator};
-use crate::utils::report_query_error;
+use crate::utils::{params::{bind_params, ParamValue, QueryParam}, query_error_message, report_query_error, sql::{split_setup, split_statements, Dialect}, tls::{tls_connector, SslMode}};
+use crate::utils::password::{ask_password, can_prompt, password_cache_key, remember_password, PasswordOptions};
+use crate::utils::pgpass;
+use crate::utils::ssh::{open_tunnel_if_enabled, SshOptions, SshTunnel, TunnelTarget};
 
 
 pub trait GetPostgresConnectionParams {
     fn get_hostname(&self) -> &Option<String>;
     fn get_username(&self) -> &Option<String>;
-    fn get_password(&self) -> &Option<String>;
+    ///password given with --password (None inside when it should be asked for)
+    fn get_given_password(&self) -> &Option<Option<String>>;
+    fn get_password_options(&self) -> &PasswordOptions;
+    fn get_ssh_options(&self) -> &SshOptions;
     fn get_port(&self) -> &Option<u16>;
     fn get_database(&self) -> &Option<String>;
     fn get_init(&self) -> &Vec<String>;
     fn get_timeout(&self) -> &Option<u64>;
+    fn get_sslmode(&self) -> &Option<String>;
+    fn get_ssl_ca(&self) -> &Option<String>;
+    fn get_ssl_cert(&self) -> &Option<String>;
+    fn get_ssl_key(&self) -> &Option<String>;
+
+    ///identifies password remembered by password agent
+    fn get_password_cache_key(&self) -> String {
+        password_cache_key("postgres", self.get_username(), self.get_hostname(), self.get_port(), self.get_database())
+    }
+
+    ///password, possibly obtained from environment, command or vault, or asked for.
+    ///If there is none, ~/.pgpass is checked, as libpq does
+    fn try_get_password(&self) -> Result<Option<String>, String> {
+        let password = self.get_password_options().resolve(self.get_given_password(), &self.get_password_cache_key())?;
+        Ok(password.or_else(|| pgpass::find_password(&pgpass::read_pgpass(), self.get_hostname(), self.get_port(), self.get_database(), self.get_username())))
+    }
+
+    ///password, exiting with error message when it can't be obtained
+    fn get_password(&self) -> Option<String> {
+        match self.try_get_password() {
+            Ok(password) => password,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 }
 
 impl GetPostgresConnectionParams for PostgresSourceOptions {
     fn get_hostname(&self) -> &Option<String> { &self.host }
     fn get_username(&self) -> &Option<String> { &self.user }
-    fn get_password(&self) -> &Option<String> { &self.password }
+    fn get_given_password(&self) -> &Option<Option<String>> { &self.password }
+    fn get_password_options(&self) -> &PasswordOptions { &self.password_options }
+    fn get_ssh_options(&self) -> &SshOptions { &self.ssh_options }
     fn get_port(&self) -> &Option<u16> { &self.port }
     fn get_database(&self) -> &Option<String> { &self.database }
     fn get_init(&self) -> &Vec<String> { &self.init }
     fn get_timeout(&self) -> &Option<u64> { &self.timeout }
+    fn get_sslmode(&self) -> &Option<String> { &self.sslmode }
+    fn get_ssl_ca(&self) -> &Option<String> { &self.ssl_ca }
+    fn get_ssl_cert(&self) -> &Option<String> { &self.ssl_cert }
+    fn get_ssl_key(&self) -> &Option<String> { &self.ssl_key }
 }
 
 impl GetPostgresConnectionParams for PostgresConfigOptions {
     fn get_hostname(&self) -> &Option<String> { &self.host }
     fn get_username(&self) -> &Option<String> { &self.user }
-    fn get_password(&self) -> &Option<String> { &self.password }
+    fn get_given_password(&self) -> &Option<Option<String>> { &self.password }
+    fn get_password_options(&self) -> &PasswordOptions { &self.password_options }
+    fn get_ssh_options(&self) -> &SshOptions { &self.ssh_options }
     fn get_port(&self) -> &Option<u16> { &self.port }
     fn get_database(&self) -> &Option<String> { &self.database }
     fn get_init(&self) -> &Vec<String> { &self.init }
     fn get_timeout(&self) -> &Option<u64> { &self.timeout }
+    fn get_sslmode(&self) -> &Option<String> { &self.sslmode }
+    fn get_ssl_ca(&self) -> &Option<String> { &self.ssl_ca }
+    fn get_ssl_cert(&self) -> &Option<String> { &self.ssl_cert }
+    fn get_ssl_key(&self) -> &Option<String> { &self.ssl_key }
 }
 
 
 pub fn get_postgres_url(postgres_options: &dyn GetPostgresConnectionParams) -> String {
+    build_postgres_url(postgres_options, postgres_options.get_hostname(), postgres_options.get_port(), &postgres_options.get_password())
+}
+
+///url with given host, port and password instead of ones from options
+pub fn build_postgres_url(postgres_options: &dyn GetPostgresConnectionParams, hostname: &Option<String>, port: &Option<u16>, password: &Option<String>) -> String {
     format!(
         "postgres://{user}{password}{hostname}{port}{database}",
-        user=match &postgres_options.get_username() { None => "", Some(v) => v},
-        hostname=match &postgres_options.get_hostname() {
+        user=match &postgres_options.get_username() {
+            None => "".to_string(),
+            Some(v) => urlencoding::encode(v).to_string()
+        },
+        hostname=match hostname {
             None => "".to_string(),
             Some(v) => format!("@{}", urlencoding::encode(v))
         },
-        password=match &postgres_options.get_password() {
+        password=match password {
             None => "".to_string(),
             Some(p) => format!(":{}", urlencoding::encode(p))
         },
-        port=match &postgres_options.get_port() {
+        port=match port {
             None => "".to_string(),
             Some(p) => format!(":{}", p)
         },
@@ -66,25 +121,155 @@ pub fn get_postgres_url(postgres_options: &dyn GetPostgresConnectionParams) -> S
 }
 
 
-pub fn establish_postgres_connection(postgres_options: &dyn GetPostgresConnectionParams) -> Client {
+///database address as seen from ssh host, host starting with / is directory of unix socket
+pub fn postgres_tunnel_target(postgres_options: &dyn GetPostgresConnectionParams) -> TunnelTarget {
+    let port = postgres_options.get_port().unwrap_or(5432);
+    match postgres_options.get_hostname() {
+        Some(host) if host.starts_with('/') => TunnelTarget::Socket(format!("{}/.s.PGSQL.{}", host.trim_end_matches('/'), port)),
+        Some(host) => TunnelTarget::Tcp(host.clone(), port),
+        None => TunnelTarget::Tcp("localhost".to_string(), port),
+    }
+}
+
+///TLS connector checking server certificate against given host name instead of one connected to,
+///so that verify-full works through ssh tunnel, which is reached at 127.0.0.1
+struct HostTlsConnector {
+    connector: MakeTlsConnector,
+    host: Option<String>,
+}
+
+impl<S> MakeTlsConnect<S> for HostTlsConnector
+where MakeTlsConnector: MakeTlsConnect<S>,
+{
+    type Stream = <MakeTlsConnector as MakeTlsConnect<S>>::Stream;
+    type TlsConnect = <MakeTlsConnector as MakeTlsConnect<S>>::TlsConnect;
+    type Error = <MakeTlsConnector as MakeTlsConnect<S>>::Error;
 
-    let database_url = get_postgres_url(postgres_options);
-    let mut client = Client::connect(&database_url, NoTls).unwrap();
+    fn make_tls_connect(&mut self, domain: &str) -> Result<Self::TlsConnect, Self::Error> {
+        self.connector.make_tls_connect(self.host.as_deref().unwrap_or(domain))
+    }
+}
 
-    if !postgres_options.get_init().is_empty() {
-        for sql in postgres_options.get_init().iter() {
-            match client.execute(sql.as_str(), &[]) {
-                Ok(_) => {},
-                Err(e) => {
-                    report_query_error(&sql, &format!("{:?}", e));
-                    std::process::exit(1);
+fn connect(postgres_options: &dyn GetPostgresConnectionParams, tunnel: &Option<SshTunnel>, password: &Option<String>, sslmode: SslMode, connector: &Option<TlsConnector>) -> Result<Client, postgres::Error> {
+    let database_url = match tunnel {
+        Some(tunnel) => build_postgres_url(postgres_options, &Some("127.0.0.1".to_string()), &Some(tunnel.local_port), password),
+        None => build_postgres_url(postgres_options, postgres_options.get_hostname(), postgres_options.get_port(), password),
+    };
+    let mut config: postgres::Config = database_url.parse()?;
+    if let Some(timeout) = postgres_options.get_timeout() {
+        config.connect_timeout(Duration::from_secs(*timeout));
+    }
+    match connector {
+        None => config.connect(NoTls),
+        Some(connector) => {
+            config.ssl_mode(if sslmode == SslMode::Prefer { PgSslMode::Prefer } else { PgSslMode::Require });
+            let host = tunnel.as_ref().map(|_| postgres_options.get_hostname().clone().unwrap_or_else(|| "localhost".to_string()));
+            config.connect(HostTlsConnector { connector: MakeTlsConnector::new(connector.clone()), host })
+        },
+    }
+}
+
+///wrong password, or server asked for one and none was given
+fn is_auth_error(error: &postgres::Error) -> bool {
+    //missing password is reported as configuration error, which can't be matched otherwise
+    error.code() == Some(&SqlState::INVALID_PASSWORD) || error.to_string().contains("password missing")
+}
+
+///connect to database, through ssh tunnel if ssh host is given. Tunnel has to be kept while connection is used.
+///With interactive set, password is asked for when it's missing or rejected
+pub fn try_establish_postgres_connection(postgres_options: &dyn GetPostgresConnectionParams, interactive: bool) -> Result<(Client, Option<SshTunnel>), String> {
+
+    let cache_key = postgres_options.get_password_cache_key();
+    let sslmode = SslMode::parse(postgres_options.get_sslmode())?;
+    let connector = match sslmode {
+        SslMode::Disable => None,
+        sslmode => Some(tls_connector(sslmode, postgres_options.get_ssl_ca(), postgres_options.get_ssl_cert(), postgres_options.get_ssl_key())?),
+    };
+    let mut password = postgres_options.try_get_password()?;
+    let tunnel = open_tunnel_if_enabled(postgres_options.get_ssh_options(), postgres_tunnel_target(postgres_options), postgres_options.get_timeout())?;
+    let mut asked = postgres_options.get_given_password() == &Some(None);
+    let mut attempts = 0;
+    //on authentication failure password is asked for, up to 3 times
+    let mut client = loop {
+        match connect(postgres_options, &tunnel, &password, sslmode, &connector) {
+            Ok(client) => {
+                if asked {
+                    remember_password(&cache_key, password.as_deref().unwrap_or(""));
                 }
-            }
+                break client;
+            },
+            Err(e) if is_auth_error(&e) && attempts < 3 && interactive && can_prompt() => {
+                if password.is_some() {
+                    eprintln!("Error: can't connect to postgres: {}", e);
+                }
+                password = Some(ask_password(&cache_key, attempts == 0 && password.is_none())?);
+                asked = true;
+                attempts += 1;
+            },
+            Err(e) => return Err(format!("can't connect to postgres: {}", e)),
+        }
+    };
+
+    for sql in postgres_options.get_init().iter() {
+        if let Err(e) = client.execute(sql.as_str(), &[]) {
+            return Err(query_error_message(sql, &format!("{:?}", e)));
         }
     }
-    client
+    Ok((client, tunnel))
 }
 
+///connect to database, exiting with error message if that fails
+pub fn establish_postgres_connection(postgres_options: &dyn GetPostgresConnectionParams) -> (Client, Option<SshTunnel>) {
+    match try_establish_postgres_connection(postgres_options, true) {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+
+pub fn postgres_param_value(param: &QueryParam) -> Box<dyn ToSql + Sync> {
+    match &param.value {
+        ParamValue::Int(value) => Box::new(*value),
+        ParamValue::Float(value) => Box::new(*value),
+        ParamValue::Bool(value) => Box::new(*value),
+        ParamValue::String(value) => Box::new(value.clone()),
+        ParamValue::Null => Box::new(None::<String>),
+    }
+}
+
+
+///replace :name placeholders with numbered ones. Numbers and booleans are cast, so that postgres doesn't need
+///to infer their types, strings and nulls get casts given for their positions, if any.
+///Every placeholder gets its own position, since the same parameter can be used as different types
+fn postgres_bind_params<'a>(query: &str, params: &'a [QueryParam], text_casts: &HashMap<usize, String>) -> (String, Vec<&'a QueryParam>) {
+    let placeholder = |position, param: &QueryParam| match (param.value.type_name(), text_casts.get(&position)) {
+        (Some(type_name), _) => format!("${}::{}", position, type_name),
+        (None, Some(cast)) => format!("${}{}", position, cast),
+        (None, None) => format!("${}", position),
+    };
+    match bind_params(query, params, Dialect::Postgres, false, placeholder) {
+        Ok(bound) => bound,
+        Err(e) => {
+            report_query_error(query, &e);
+            std::process::exit(1);
+        }
+    }
+}
+
+
+///position of parameter postgres couldn't infer type of, taken from error message
+///("could not determine data type of parameter $1", number is kept in translations)
+fn undetermined_parameter(error: &postgres::Error) -> Option<usize> {
+    let db_error = std::error::Error::source(error)?.downcast_ref::<DbError>()?;
+    if db_error.code() != &SqlState::INDETERMINATE_DATATYPE {
+        return None;
+    }
+    let message = db_error.message();
+    message[message.rfind('$')? + 1..].chars().take_while(|c| c.is_ascii_digit()).collect::<String>().parse().ok()
+}
 
 
 pub struct PostgresSource {
@@ -94,8 +279,9 @@ pub struct PostgresSource {
 pub struct PostgresSourceConnection<'c> {
     connection: Client,
     //results: postgres::RowIter<'c>,//Vec<postgres::row::Row>,
-    query: String,
     source: &'c  PostgresSource,
+    //kept open while connection is used
+    _tunnel: Option<SshTunnel>,
 }
 
 pub struct PostgresSourceBatchIterator<'i>
@@ -120,23 +306,12 @@ where 'c: 'i,
     fn connect(&'c self) -> PostgresSourceConnection
     {
         
-        let connection =  establish_postgres_connection(&self.options);
-        let query = match &self.options.query {
-            Some(q) => q.to_owned(),
-            None => match &self.options.query_file {
-                Some(path_buf) => {
-                    let mut sql = String::new();
-                    File::open(path_buf).unwrap().read_to_string(&mut sql).unwrap();
-                    sql
-                },
-                None => panic!("You need to pass either q or query-file option"),
-            }
-        };
+        let (connection, tunnel) =  establish_postgres_connection(&self.options);
 
         PostgresSourceConnection {
             connection,
             source: &self,
-            query: query
+            _tunnel: tunnel,
             //results,
         }
     }
@@ -147,14 +322,67 @@ where 'c: 'i,
 
 }
 
+impl <'c> PostgresSourceConnection<'c> {
+
+    ///replace :name placeholders, returning query and values to bind
+    fn bind_query(&mut self, query: &str) -> (String, Vec<Box<dyn ToSql + Sync>>) {
+        let params = &self.source.options.params;
+        let mut text_casts = HashMap::new();
+        let (mut sql, bound_params) = postgres_bind_params(query, params, &text_casts);
+        //strings and nulls are sent as text, so where postgres infers other type for them (date, numeric...)
+        //they have to be cast from text. Where it can't infer any type, they are text
+        if bound_params.iter().any(|param| param.value.type_name().is_none()) {
+            let statement = loop {
+                match self.connection.prepare(&sql) {
+                    Ok(statement) => break statement,
+                    Err(e) => match undetermined_parameter(&e) {
+                        Some(position) if !text_casts.contains_key(&position) => {
+                            text_casts.insert(position, "::text".to_string());
+                            sql = postgres_bind_params(query, params, &text_casts).0;
+                        },
+                        _ => {
+                            report_query_error(&sql, &format!("{:?}", e));
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            };
+            for (idx, (param, param_type)) in bound_params.iter().zip(statement.params()).enumerate() {
+                if param.value.type_name().is_none() && !<String as ToSql>::accepts(param_type) {
+                    text_casts.insert(idx + 1, format!("::text::\"{}\".\"{}\"", param_type.schema(), param_type.name()));
+                }
+            }
+            sql = postgres_bind_params(query, params, &text_casts).0;
+        }
+        (sql, bound_params.iter().map(|param| postgres_param_value(param)).collect())
+    }
+}
+
 impl <'c, 'i>DataSourceConnection<'i, PostgresSourceBatchIterator<'i>> for PostgresSourceConnection<'c>
 {
-    fn batch_iterator(&'i mut self, batch_size: u64) -> PostgresSourceBatchIterator<'i>
+    fn execute(&mut self, sql: &str) {
+        for statement in split_statements(sql, Dialect::Postgres) {
+            let (statement, values) = self.bind_query(&statement);
+            let values: Vec<&(dyn ToSql + Sync)> = values.iter().map(|value| value.as_ref()).collect();
+            if let Err(e) = self.connection.execute(statement.as_str(), &values) {
+                report_query_error(&statement, &format!("{:?}", e));
+                std::process::exit(1);
+            }
+        }
+    }
+
+    fn batch_iterator(&'i mut self, query: &str, batch_size: u64) -> PostgresSourceBatchIterator<'i>
     {
-         let results = {match self.connection.query_raw(self.query.as_str(), std::iter::empty()) {
+        //all statements but last one prepare data for it
+        let (setup, query) = split_setup(query, Dialect::Postgres);
+        for statement in setup.iter() {
+            self.execute(statement);
+        }
+        let (query, values) = self.bind_query(&query);
+        let results = {match self.connection.query_raw(query.as_str(), values.iter().map(|value| value.as_ref() as &dyn ToSql)) {
             Ok(r) => r,
             Err(e) => {
-                report_query_error(&self.query, &format!("{:?}", e));
+                report_query_error(&query, &format!("{:?}", e));
                 std::process::exit(1);
             }
         }};
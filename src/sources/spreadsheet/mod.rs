@@ -0,0 +1,280 @@
+use calamine::{self, DataType, Reader};
+use chrono::Timelike;
+
+use crate::commands::export::SpreadSheetSourceOptions;
+use crate::definitions::{ColumnType, Value, Row, ColumnInfo, DataSource, DataSourceConnection, DataSourceBatchIterator};
+
+
+///parse cell reference such as B12 into (row, column), both counted from 0
+pub fn parse_cell_reference(reference: &str) -> Result<(u32, u32), String> {
+    let letters: String = reference.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+    let digits = &reference[letters.len()..];
+    if letters.is_empty() || digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("invalid cell reference: {}", reference));
+    }
+    let column = letters
+        .to_ascii_uppercase()
+        .bytes()
+        .try_fold(0u32, |acc, letter| acc.checked_mul(26)?.checked_add(u32::from(letter - b'A' + 1)))
+        .ok_or_else(|| format!("invalid cell reference: {}", reference))?;
+    let row: u32 = digits.parse().map_err(|_| format!("invalid cell reference: {}", reference))?;
+    if row == 0 {
+        return Err(format!("invalid cell reference: {}", reference));
+    }
+    Ok((row - 1, column - 1))
+}
+
+///parse range such as A1:F2000 into top left and bottom right cell
+pub fn parse_cell_range(range: &str) -> Result<((u32, u32), (u32, u32)), String> {
+    let mut parts = range.splitn(2, ':');
+    let start = parse_cell_reference(parts.next().unwrap())?;
+    let end = match parts.next() {
+        Some(end) => parse_cell_reference(end)?,
+        None => return Err(format!("expected range like A1:F2000, got: {}", range)),
+    };
+    if end.0 < start.0 || end.1 < start.1 {
+        return Err(format!("invalid range: {}", range));
+    }
+    Ok((start, end))
+}
+
+fn cell_type(cell: &calamine::Data) -> Option<ColumnType> {
+    match cell {
+        calamine::Data::Empty | calamine::Data::Error(_) => None,
+        calamine::Data::Int(_) => Some(ColumnType::I64),
+        //xlsx stores all numbers as floats
+        calamine::Data::Float(value) if value.fract() == 0.0 && value.abs() < 1e15 => Some(ColumnType::I64),
+        calamine::Data::Float(_) => Some(ColumnType::F64),
+        calamine::Data::Bool(_) => Some(ColumnType::Bool),
+        calamine::Data::String(_) | calamine::Data::DurationIso(_) => Some(ColumnType::String),
+        calamine::Data::DateTime(_) | calamine::Data::DateTimeIso(_) => match cell.as_datetime() {
+            //excel has no separate date type, date is datetime at midnight
+            Some(datetime) if datetime.time().num_seconds_from_midnight() == 0 && datetime.time().nanosecond() == 0 => Some(ColumnType::Date),
+            Some(_) => Some(ColumnType::DateTime),
+            None => Some(ColumnType::String),
+        },
+    }
+}
+
+///find type matching all non-empty cells. Mixed types are exported as text
+pub fn infer_column_type<'a>(cells: impl Iterator<Item=&'a calamine::Data>) -> ColumnType {
+    let mut result: Option<ColumnType> = None;
+    for cell in cells {
+        let value_type = match cell_type(cell) {
+            Some(value_type) => value_type,
+            None => continue,
+        };
+        result = Some(match result {
+            None => value_type,
+            Some(column_type) if column_type == value_type => column_type,
+            Some(ColumnType::I64) | Some(ColumnType::F64) if value_type == ColumnType::I64 || value_type == ColumnType::F64 => ColumnType::F64,
+            Some(ColumnType::Date) | Some(ColumnType::DateTime) if value_type == ColumnType::Date || value_type == ColumnType::DateTime => ColumnType::DateTime,
+            Some(_) => return ColumnType::String,
+        });
+    }
+    result.unwrap_or(ColumnType::String)
+}
+
+///cells that do not match column type are kept as text
+pub fn cell_to_value(cell: &calamine::Data, column_type: &ColumnType) -> Value {
+    match (cell, column_type) {
+        (calamine::Data::Empty, _) | (calamine::Data::Error(_), _) => Value::None,
+        (calamine::Data::Int(value), ColumnType::I64) => Value::I64(*value),
+        (calamine::Data::Int(value), ColumnType::F64) => Value::F64(*value as f64),
+        (calamine::Data::Float(value), ColumnType::F64) => Value::F64(*value),
+        (calamine::Data::Float(value), ColumnType::I64) => Value::I64(*value as i64),
+        (calamine::Data::Bool(value), ColumnType::Bool) => Value::Bool(*value),
+        (_, ColumnType::Date) if cell.as_date().is_some() => Value::Date(cell.as_date().unwrap()),
+        (_, ColumnType::DateTime) if cell.as_datetime().is_some() => Value::DateTime(cell.as_datetime().unwrap()),
+        (calamine::Data::String(value), _) => Value::String(value.clone()),
+        (cell, _) => Value::String(cell.to_string()),
+    }
+}
+
+
+pub struct SpreadSheetSource {
+    options: SpreadSheetSourceOptions,
+}
+
+pub struct SpreadSheetSourceConnection<'c> {
+    sheet: calamine::Range<calamine::Data>,
+    source: &'c SpreadSheetSource,
+}
+
+pub struct SpreadSheetSourceBatchIterator<'i> {
+    batch_size: u64,
+    columns: Vec<ColumnInfo>,
+    sheet: &'i calamine::Range<calamine::Data>,
+    //absolute positions of first and last column, next and last row
+    first_column: u32,
+    last_column: u32,
+    next_row: u32,
+    last_row: u32,
+}
+
+impl SpreadSheetSource {
+    pub fn init(spreadsheet_options: &SpreadSheetSourceOptions) -> SpreadSheetSource {
+        SpreadSheetSource { options: spreadsheet_options.to_owned() }
+    }
+
+    fn read_sheet(&self) -> calamine::Range<calamine::Data> {
+        let mut workbook = match calamine::open_workbook_auto(&self.options.filename) {
+            Ok(workbook) => workbook,
+            Err(e) => {
+                eprintln!("Error: could not open {}: {}", self.options.filename, e);
+                std::process::exit(1);
+            }
+        };
+        let sheet_names = workbook.sheet_names().to_owned();
+        let sheet_name = match &self.options.sheet {
+            None => sheet_names.first().cloned(),
+            Some(name) if sheet_names.contains(name) => Some(name.clone()),
+            //sheets can also be chosen by number, counting from 1
+            Some(name) => match name.parse::<usize>() {
+                Ok(idx) if idx > 0 => sheet_names.get(idx - 1).cloned(),
+                _ => None,
+            },
+        };
+        let sheet_name = match sheet_name {
+            Some(sheet_name) => sheet_name,
+            None => {
+                eprintln!(
+                    "Error: sheet {} not found in {}. Available sheets: {}",
+                    self.options.sheet.as_deref().unwrap_or("1"),
+                    self.options.filename,
+                    sheet_names.join(", "),
+                );
+                std::process::exit(1);
+            }
+        };
+        match workbook.worksheet_range(&sheet_name) {
+            Ok(range) => range,
+            Err(e) => {
+                eprintln!("Error: could not read sheet {} from {}: {}", sheet_name, self.options.filename, e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+
+impl <'c, 'i> DataSource<'c, 'i, SpreadSheetSourceConnection<'c>, SpreadSheetSourceBatchIterator<'i>> for SpreadSheetSource
+where 'c: 'i,
+{
+    fn connect(&'c self) -> SpreadSheetSourceConnection<'c>
+    {
+        SpreadSheetSourceConnection {
+            sheet: self.read_sheet(),
+            source: &self,
+        }
+    }
+
+    fn get_type_name(&self) -> String {"spreadsheet".to_string()}
+    fn get_name(&self) -> String { "spreadsheet".to_string() }
+}
+
+impl <'c, 'i>DataSourceConnection<'i, SpreadSheetSourceBatchIterator<'i>> for SpreadSheetSourceConnection<'c>
+where 'c: 'i,
+{
+    ///spreadsheet source has no query language, whole sheet (or selected range) is returned
+    fn batch_iterator(&'i mut self, _query: &str, batch_size: u64) -> SpreadSheetSourceBatchIterator<'i>
+    {
+        let options = &self.source.options;
+        let (start, end) = match (&options.range, self.sheet.start(), self.sheet.end()) {
+            (Some(range), _, _) => *range,
+            (None, Some(start), Some(end)) => (start, end),
+            //empty sheet
+            (None, _, _) => ((0, 0), (0, 0)),
+        };
+        let header_row = match options.header_row {
+            Some(0) => None,
+            Some(row) => Some(row - 1),
+            None => Some(start.0),
+        };
+        if let Some(row) = header_row {
+            if row > end.0 && !self.sheet.is_empty() {
+                eprintln!("Error: header row {} is below the last row of data ({})", row + 1, end.0 + 1);
+                std::process::exit(1);
+            }
+        }
+        //header can be given above selected range, rows outside of it are not exported
+        let first_data_row = match header_row {
+            Some(row) => (row + 1).max(start.0),
+            None => start.0,
+        };
+        let columns = (start.1..=end.1)
+            .map(|column| {
+                let name = header_row
+                    .and_then(|row| self.sheet.get_value((row, column)))
+                    .map(|cell| cell.to_string())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("column_{}", column - start.1 + 1));
+                let data_type = infer_column_type(
+                    (first_data_row..=end.0).filter_map(|row| self.sheet.get_value((row, column)))
+                );
+                ColumnInfo { name, data_type }
+            })
+            .collect();
+
+        SpreadSheetSourceBatchIterator {
+            batch_size,
+            columns,
+            sheet: &self.sheet,
+            first_column: start.1,
+            last_column: end.1,
+            next_row: first_data_row,
+            last_row: if self.sheet.is_empty() { 0 } else { end.0 },
+        }
+    }
+}
+
+
+impl <'i>DataSourceBatchIterator for SpreadSheetSourceBatchIterator<'i>
+{
+    fn get_column_info(&self) -> Vec<ColumnInfo> {
+        self.columns.clone()
+    }
+
+    fn get_count(&self) -> Option<u64> {
+        Some(u64::from(self.last_row + 1).saturating_sub(u64::from(self.next_row)))
+    }
+
+    fn next(&mut self) -> Option<Vec<Row>>
+    {
+        let mut rows = vec![];
+        while rows.len() < self.batch_size as usize && self.next_row <= self.last_row && !self.sheet.is_empty() {
+            let row = (self.first_column..=self.last_column)
+                .zip(self.columns.iter())
+                .map(|(column, column_info)| match self.sheet.get_value((self.next_row, column)) {
+                    Some(cell) => cell_to_value(cell, &column_info.data_type),
+                    None => Value::None,
+                })
+                .collect();
+            rows.push(row);
+            self.next_row += 1;
+        }
+        if !rows.is_empty() {
+            Some(rows)
+        } else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{parse_cell_range, parse_cell_reference};
+
+    #[test]
+    fn test_parse_cell_range() {
+        assert_eq!(parse_cell_reference("A1"), Ok((0, 0)));
+        assert_eq!(parse_cell_reference("ab12"), Ok((11, 27)));
+        assert!(parse_cell_reference("12").is_err());
+        assert_eq!(parse_cell_reference("ZZZZZZZZ1"), Err("invalid cell reference: ZZZZZZZZ1".to_string()));
+        assert_eq!(parse_cell_range("B2:F2000"), Ok(((1, 1), (1999, 5))));
+        assert!(parse_cell_range("F1:A1").is_err());
+    }
+
+}
@@ -1,11 +1,8 @@
-use std::fs::File;
-use std::io::Read;
-
 use sqlite;
 
 use crate::commands::{common::SqliteConfigOptions, export::SqliteSourceOptions};
 use crate::definitions::{ColumnType, Value, Row, ColumnInfo, DataSource, DataSourceConnection, DataSourceBatchIterator};
-use crate::utils::report_query_error;
+use crate::utils::{params::{bind_params, ParamValue}, report_query_error, sql::{split_setup, split_statements, Dialect}};
 
 pub trait GetSqliteConnectionParams {
     fn get_filename(&self) -> &Option<String>;
@@ -22,13 +19,23 @@ impl GetSqliteConnectionParams for SqliteConfigOptions {
     fn get_init(&self) -> &Vec<String> { &self.init }
 }
 
-pub fn establish_sqlite_connection(options: &dyn GetSqliteConnectionParams) -> sqlite::Connection{
+pub fn try_establish_sqlite_connection(options: &dyn GetSqliteConnectionParams) -> Result<sqlite::Connection, String> {
     sqlite::Connection::open(
         options
             .get_filename()
             .to_owned()
             .unwrap_or_else(||":memory:".to_string())
-    ).unwrap()
+    ).map_err(|e| format!("could not open sqlite database: {}", e))
+}
+
+pub fn establish_sqlite_connection(options: &dyn GetSqliteConnectionParams) -> sqlite::Connection{
+    match try_establish_sqlite_connection(options) {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 pub struct SqliteSource {
@@ -89,34 +96,72 @@ where 'c: 'i,
 
 }
 
+impl <'c> SqliteSourceConnection<'c> {
+
+    ///prepare statement and bind values of its :name placeholders
+    fn prepare_statement(&self, query: &str) -> sqlite::Statement<'_> {
+        let (query, bound_params) = match bind_params(query, &self.source.options.params, Dialect::Sqlite, true, |position, _| format!("?{}", position)) {
+            Ok(v) => v,
+            Err(e) => {
+                report_query_error(query, &e);
+                std::process::exit(1);
+            }
+        };
+        let mut statement = match self.connection.prepare(&query) {
+            Ok(v) => v,
+            Err(e) => {
+                report_query_error(&query, &format!("{:?}", e));
+                std::process::exit(1);
+            }
+        };
+        for (idx, param) in bound_params.iter().enumerate() {
+            let result = match &param.value {
+                ParamValue::Int(value) => statement.bind(idx + 1, *value),
+                ParamValue::Float(value) => statement.bind(idx + 1, *value),
+                ParamValue::Bool(value) => statement.bind(idx + 1, *value as i64),
+                ParamValue::String(value) => statement.bind(idx + 1, value.as_str()),
+                ParamValue::Null => statement.bind(idx + 1, ()),
+            };
+            if let Err(e) = result {
+                report_query_error(&query, &format!("{:?}", e));
+                std::process::exit(1);
+            }
+        }
+        statement
+    }
+}
+
 impl <'c, 'i>DataSourceConnection<'i, SqliteSourceBatchIterator<'i>> for SqliteSourceConnection<'c>
 {
-    fn batch_iterator(&'i mut self, batch_size: u64) -> SqliteSourceBatchIterator<'i>
-    {
-        let query = match &self.source.options.query {
-            Some(q) => q.to_owned(),
-            None => match &self.source.options.query_file {
-                Some(path_buf) => {
-                    let mut sql = String::new();
-                    File::open(path_buf).unwrap().read_to_string(&mut sql).unwrap();
-                    sql
-                },
-                None => panic!("You need to pass either q or query-file option"),
+    fn execute(&mut self, sql: &str) {
+        for query in split_statements(sql, Dialect::Sqlite) {
+            let mut statement = self.prepare_statement(&query);
+            loop {
+                match statement.next() {
+                    Ok(sqlite::State::Row) => {},
+                    Ok(sqlite::State::Done) => break,
+                    Err(e) => {
+                        report_query_error(&query, &format!("{:?}", e));
+                        std::process::exit(1);
+                    }
+                }
             }
-        };
+        }
+    }
 
+    fn batch_iterator(&'i mut self, query: &str, batch_size: u64) -> SqliteSourceBatchIterator<'i>
+    {
+        //all statements but last one prepare data for it
+        let (setup, query) = split_setup(query, Dialect::Sqlite);
+        for statement in setup.iter() {
+            self.execute(statement);
+        }
         SqliteSourceBatchIterator {
             batch_size,
             connection: &self.connection,
             count: None,
             done: false,
-            statement: match self.connection.prepare(&query) {
-                Ok(v) => v,
-                Err(e) => {
-                    report_query_error(&query, &format!("{:?}", e));
-                    std::process::exit(1);
-                }
-            },
+            statement: self.prepare_statement(&query),
             //source_connection: &self,
         }
     }
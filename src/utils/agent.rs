@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use base64;
+
+use crate::config;
+
+
+//passwords asked for on terminal are kept in memory of agent process, reachable through unix socket
+//in directory accessible only by current user. Agent exits after timeout.
+//Protocol is one request line per connection: PING, GET key, SET key password or STOP, values base64-encoded.
+
+pub fn get_agent_directory() -> PathBuf {
+    config::get_config_directory().join("agent")
+}
+
+pub fn get_socket_path() -> PathBuf {
+    get_agent_directory().join("socket")
+}
+
+fn encode(text: &str) -> String {
+    base64::encode(text.as_bytes())
+}
+
+fn decode(text: &str) -> Option<String> {
+    base64::decode(text).ok().and_then(|data| String::from_utf8(data).ok())
+}
+
+///send single request line to agent, returning its response
+fn request(line: &str) -> Option<String> {
+    let mut stream = UnixStream::connect(get_socket_path()).ok()?;
+    stream.set_read_timeout(Some(Duration::from_secs(2))).ok()?;
+    stream.write_all(format!("{}\n", line).as_bytes()).ok()?;
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response).ok()?;
+    Some(response.trim_end().to_string())
+}
+
+pub fn is_running() -> bool {
+    request("PING").as_deref() == Some("OK")
+}
+
+///get password cached by agent, if it's running
+pub fn get(key: &str) -> Option<String> {
+    request(&format!("GET {}", encode(key)))?
+        .strip_prefix("OK ")
+        .and_then(decode)
+}
+
+///cache password in agent, does nothing if agent is not running
+pub fn set(key: &str, password: &str) {
+    request(&format!("SET {} {}", encode(key), encode(password)));
+}
+
+pub fn stop() -> bool {
+    request("STOP").as_deref() == Some("OK")
+}
+
+fn handle(stream: UnixStream, passwords: &mut HashMap<String, String>) -> bool {
+    let mut line = String::new();
+    if BufReader::new(&stream).read_line(&mut line).is_err() {
+        return true;
+    }
+    let parts: Vec<&str> = line.trim_end().split(' ').collect();
+    let (response, keep_running) = match parts.as_slice() {
+        ["PING"] => ("OK".to_string(), true),
+        ["GET", key] => match decode(key).and_then(|key| passwords.get(&key)) {
+            Some(password) => (format!("OK {}", encode(password)), true),
+            None => ("NONE".to_string(), true),
+        },
+        ["SET", key, password] => match (decode(key), decode(password)) {
+            (Some(key), Some(password)) => {
+                passwords.insert(key, password);
+                ("OK".to_string(), true)
+            },
+            _ => ("ERROR".to_string(), true),
+        },
+        ["STOP"] => ("OK".to_string(), false),
+        _ => ("ERROR".to_string(), true),
+    };
+    let _ = (&stream).write_all(format!("{}\n", response).as_bytes());
+    keep_running
+}
+
+///run agent in current process, serving requests until stopped or timeout (in seconds) passes
+pub fn run(timeout: u64) -> Result<(), String> {
+    let directory = get_agent_directory();
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&directory)
+        .map_err(|e| format!("can't create {}: {}", directory.display(), e))?;
+    let socket_path = get_socket_path();
+    if socket_path.exists() {
+        if is_running() {
+            return Err("password agent is already running".to_string());
+        }
+        //left by agent that didn't exit cleanly
+        let _ = std::fs::remove_file(&socket_path);
+    }
+    let listener = UnixListener::bind(&socket_path).map_err(|e| format!("can't listen on {}: {}", socket_path.display(), e))?;
+    let expired_socket_path = socket_path.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_secs(timeout));
+        let _ = std::fs::remove_file(&expired_socket_path);
+        std::process::exit(0);
+    });
+    let mut passwords = HashMap::new();
+    for stream in listener.incoming() {
+        if let Ok(stream) = stream {
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+            if !handle(stream, &mut passwords) {
+                break;
+            }
+        }
+    }
+    let _ = std::fs::remove_file(&socket_path);
+    Ok(())
+}
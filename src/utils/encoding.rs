@@ -0,0 +1,202 @@
+use std::io::{Read, Write};
+
+use encoding_rs;
+
+///encodings supported by text based sources and destinations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16LE,
+    Windows1250,
+}
+
+impl TextEncoding {
+
+    pub fn from_name(name: &str) -> Option<TextEncoding> {
+        match name.to_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(TextEncoding::Utf8),
+            "utf-16le" | "utf16le" => Some(TextEncoding::Utf16LE),
+            "windows-1250" | "cp1250" => Some(TextEncoding::Windows1250),
+            _ => None,
+        }
+    }
+
+    pub fn bom(self) -> &'static [u8] {
+        match self {
+            TextEncoding::Utf8 => b"\xEF\xBB\xBF",
+            TextEncoding::Utf16LE => b"\xFF\xFE",
+            TextEncoding::Windows1250 => b"",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Utf16LE => "utf-16le",
+            TextEncoding::Windows1250 => "windows-1250",
+        }
+    }
+
+    pub fn encoding(self) -> &'static encoding_rs::Encoding {
+        match self {
+            TextEncoding::Utf8 => encoding_rs::UTF_8,
+            TextEncoding::Utf16LE => encoding_rs::UTF_16LE,
+            TextEncoding::Windows1250 => encoding_rs::WINDOWS_1250,
+        }
+    }
+
+    ///encode text, characters encoding has no representation for are replaced with '?' or reported as error
+    pub fn encode(self, text: &str, replace_unmappable: bool) -> Result<Vec<u8>, String> {
+        match self {
+            TextEncoding::Utf8 => Ok(text.as_bytes().to_vec()),
+            TextEncoding::Utf16LE => Ok(text.encode_utf16().flat_map(|unit| unit.to_le_bytes().to_vec()).collect()),
+            TextEncoding::Windows1250 => {
+                let (bytes, _, had_errors) = encoding_rs::WINDOWS_1250.encode(text);
+                if !had_errors {
+                    return Ok(bytes.into_owned());
+                }
+                let mut result = Vec::with_capacity(text.len());
+                let mut buffer = [0; 4];
+                for ch in text.chars() {
+                    let (bytes, _, unmappable) = encoding_rs::WINDOWS_1250.encode(ch.encode_utf8(&mut buffer));
+                    match (unmappable, replace_unmappable) {
+                        (false, _) => result.extend_from_slice(&bytes),
+                        (true, true) => result.push(b'?'),
+                        (true, false) => return Err(format!(
+                            "character '{}' (U+{:04X}) can't be encoded as {} (use --encoding-errors replace to write '?' instead)",
+                            ch, ch as u32, self.name(),
+                        )),
+                    }
+                }
+                Ok(result)
+            },
+        }
+    }
+}
+
+
+///writer accepting utf-8 data and passing it to inner writer in selected encoding.
+///Incomplete utf-8 sequences are kept until the rest of them arrives.
+pub struct EncodingWriter<W: Write> {
+    inner: W,
+    encoding: TextEncoding,
+    replace_unmappable: bool,
+    pending: Vec<u8>,
+}
+
+impl <W: Write> EncodingWriter<W> {
+
+    pub fn new(inner: W, encoding: TextEncoding, replace_unmappable: bool) -> EncodingWriter<W> {
+        EncodingWriter { inner, encoding, replace_unmappable, pending: vec![] }
+    }
+}
+
+impl <W: Write> Write for EncodingWriter<W> {
+
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.encoding == TextEncoding::Utf8 {
+            return self.inner.write(buf);
+        }
+        self.pending.extend_from_slice(buf);
+        let valid_up_to = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            Err(e) => match e.error_len() {
+                None => e.valid_up_to(),
+                Some(_) => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+            }
+        };
+        let tail = self.pending.split_off(valid_up_to);
+        let text = std::str::from_utf8(&self.pending).unwrap();
+        let encoded = self.encoding.encode(text, self.replace_unmappable)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        self.inner.write_all(&encoded)?;
+        self.pending = tail;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+
+///reader decoding data from selected encoding to utf-8. Byte order mark, if present, is skipped.
+pub struct DecodingReader<R: Read> {
+    inner: R,
+    decoder: encoding_rs::Decoder,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    position: usize,
+    done: bool,
+}
+
+impl <R: Read> DecodingReader<R> {
+
+    pub fn new(inner: R, encoding: TextEncoding) -> DecodingReader<R> {
+        DecodingReader {
+            inner,
+            decoder: encoding.encoding().new_decoder(),
+            input: vec![0; 8192],
+            output: vec![],
+            position: 0,
+            done: false,
+        }
+    }
+}
+
+impl <R: Read> Read for DecodingReader<R> {
+
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.output.len() {
+            if self.done {
+                return Ok(0);
+            }
+            let length = self.inner.read(&mut self.input)?;
+            let last = length == 0;
+            let max_length = self.decoder.max_utf8_buffer_length(length).unwrap();
+            self.output.resize(max_length, 0);
+            let (_result, _read, written, _replaced) = self.decoder.decode_to_utf8(&self.input[..length], &mut self.output, last);
+            self.output.truncate(written);
+            self.position = 0;
+            self.done = last;
+        }
+        let length = buf.len().min(self.output.len() - self.position);
+        buf[..length].copy_from_slice(&self.output[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::{Read, Write};
+
+    use super::{DecodingReader, EncodingWriter, TextEncoding};
+
+    #[test]
+    fn test_encoding_writer_split_sequence() {
+        let mut writer = EncodingWriter::new(vec![], TextEncoding::Windows1250, false);
+        let text = "zaż".as_bytes();
+        writer.write_all(&text[..3]).unwrap();
+        writer.write_all(&text[3..]).unwrap();
+        assert_eq!(writer.inner, vec![b'z', b'a', 0xBF]);
+    }
+
+    #[test]
+    fn test_unmappable_characters() {
+        assert!(TextEncoding::Windows1250.encode("→ok", false).unwrap_err().contains("U+2192"));
+        assert_eq!(TextEncoding::Windows1250.encode("→ok", true), Ok(b"?ok".to_vec()));
+        assert!(EncodingWriter::new(vec![], TextEncoding::Windows1250, false).write_all("→".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_decoding_reader() {
+        let mut reader = DecodingReader::new(&b"\xFF\xFEz\x00|\x01"[..], TextEncoding::Utf16LE);
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "zż");
+    }
+
+}
@@ -0,0 +1,183 @@
+use std::fmt::Write;
+
+use base64;
+use chrono::format::{Item, StrftimeItems};
+
+use crate::commands::export::{FormattingOptions, TruncateOptions};
+use crate::definitions::Value;
+use crate::utils::escape_binary_data;
+use crate::utils::truncation::Truncation;
+
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+pub const DEFAULT_TIME_FORMAT: &str = "%H:%M:%S";
+pub const DEFAULT_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BytesFormat {
+    Escape,
+    Hex,
+    Base64,
+}
+
+///converts values to text the same way for every text-like destination
+#[derive(Clone, Debug)]
+pub struct ValueFormatter {
+    pub date_format: Option<String>,
+    pub time_format: Option<String>,
+    pub datetime_format: Option<String>,
+    pub float_precision: Option<usize>,
+    pub bool_format: Option<(String, String)>,
+    pub null_as: Option<String>,
+    pub bytes_as: BytesFormat,
+    pub truncation: Truncation,
+}
+
+///exit with error if format is not valid strftime syntax or uses fields sample value doesn't have (e.g. hours for dates)
+fn check_format(option: &str, format: &Option<String>, can_format_sample: impl Fn(&str) -> bool) {
+    if let Some(format) = format {
+        if StrftimeItems::new(format).any(|item| item == Item::Error) || !can_format_sample(format) {
+            eprintln!("Error: invalid --{}: {}", option, format);
+            std::process::exit(1);
+        }
+    }
+}
+
+impl ValueFormatter {
+
+    pub fn init(options: &FormattingOptions, truncate: &TruncateOptions) -> ValueFormatter {
+        let sample = chrono::NaiveDate::from_ymd_opt(2000, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        check_format("date-format", &options.date_format, |format| write!(String::new(), "{}", sample.date().format(format)).is_ok());
+        check_format("time-format", &options.time_format, |format| write!(String::new(), "{}", sample.time().format(format)).is_ok());
+        check_format("datetime-format", &options.datetime_format, |format| write!(String::new(), "{}", sample.format(format)).is_ok());
+        ValueFormatter {
+            date_format: options.date_format.clone(),
+            time_format: options.time_format.clone(),
+            datetime_format: options.datetime_format.clone(),
+            float_precision: options.float_precision,
+            bool_format: options.bool_format.as_ref().map(|format| {
+                let mut parts = format.splitn(2, '/');
+                (parts.next().unwrap().to_string(), parts.next().unwrap_or("").to_string())
+            }),
+            null_as: options.null_as.clone(),
+            bytes_as: match options.bytes_as.as_str() {
+                "hex" => BytesFormat::Hex,
+                "base64" => BytesFormat::Base64,
+                _ => BytesFormat::Escape,
+            },
+            truncation: Truncation::init(truncate),
+        }
+    }
+
+    pub fn format_float(&self, value: f64) -> String {
+        match self.float_precision {
+            None => value.to_string(),
+            Some(precision) => format!("{:.*}", precision, value),
+        }
+    }
+
+    ///round float to configured precision, for destinations that store numbers natively
+    pub fn round_float(&self, value: f64) -> f64 {
+        match self.float_precision {
+            None => value,
+            Some(precision) => format!("{:.*}", precision, value).parse().unwrap_or(value),
+        }
+    }
+
+    pub fn format_bool(&self, value: bool) -> String {
+        match &self.bool_format {
+            None => value.to_string(),
+            Some((true_text, false_text)) => if value { true_text.clone() } else { false_text.clone() },
+        }
+    }
+
+    pub fn format_bytes(&self, value: &[u8]) -> String {
+        match self.bytes_as {
+            BytesFormat::Escape => escape_binary_data(value),
+            BytesFormat::Hex => value.iter().map(|b| format!("{:02x}", b)).collect(),
+            BytesFormat::Base64 => base64::encode(value),
+        }
+    }
+
+    pub fn format_date(&self, date: &chrono::NaiveDate) -> String {
+        date.format(self.date_format.as_deref().unwrap_or(DEFAULT_DATE_FORMAT)).to_string()
+    }
+
+    pub fn format_time(&self, time: &chrono::NaiveTime) -> String {
+        time.format(self.time_format.as_deref().unwrap_or(DEFAULT_TIME_FORMAT)).to_string()
+    }
+
+    pub fn format_datetime(&self, datetime: &chrono::NaiveDateTime) -> String {
+        datetime.format(self.datetime_format.as_deref().unwrap_or(DEFAULT_DATETIME_FORMAT)).to_string()
+    }
+
+    ///resolve per column truncation for new results
+    pub fn set_columns(&mut self, column_names: &[String]) {
+        self.truncation.set_columns(column_names);
+    }
+
+    pub fn format(&self, value: &Value) -> String {
+        self.format_column(usize::MAX, value)
+    }
+
+    ///format value of column with given index, applying its truncation limit
+    pub fn format_column(&self, idx: usize, value: &Value) -> String {
+        match value {
+            Value::U64(value) => value.to_string(),
+            Value::I64(value) => value.to_string(),
+            Value::U32(value) => value.to_string(),
+            Value::I32(value) => value.to_string(),
+            Value::U16(value) => value.to_string(),
+            Value::I16(value) => value.to_string(),
+            Value::U8(value) => value.to_string(),
+            Value::I8(value) => value.to_string(),
+            Value::F64(value) => self.format_float(*value),
+            Value::F32(value) => self.format_float(f64::from(*value)),
+            Value::String(value) => self.truncation.truncate_string(idx, value),
+            Value::Bool(value) => self.format_bool(*value),
+            Value::Bytes(value) => {
+                let truncated = self.truncation.truncate_bytes(idx, value);
+                if truncated.len() < value.len() {
+                    format!("{} ...({} bytes trimmed)", self.format_bytes(truncated), value.len() - truncated.len())
+                } else {
+                    self.format_bytes(value)
+                }
+            },
+            Value::JSON(value) => self.truncation.truncate_string(idx, value),
+            Value::None => self.null_as.clone().unwrap_or_default(),
+            Value::Timestamp(value) => value.to_string(),
+            Value::Date(date) => self.format_date(date),
+            Value::Time(time) => self.format_time(time),
+            Value::DateTime(datetime) => self.format_datetime(datetime),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{BytesFormat, ValueFormatter};
+    use crate::utils::truncation::Truncation;
+    use crate::definitions::Value;
+
+    #[test]
+    fn test_format_values() {
+        let formatter = ValueFormatter {
+            date_format: Some("%d.%m.%Y".to_string()),
+            time_format: None,
+            datetime_format: None,
+            float_precision: Some(2),
+            bool_format: Some(("yes".to_string(), "no".to_string())),
+            null_as: Some("NULL".to_string()),
+            bytes_as: BytesFormat::Hex,
+            truncation: Truncation::default(),
+        };
+        assert_eq!(formatter.format(&Value::F64(1.0 / 3.0)), "0.33");
+        assert_eq!(formatter.format(&Value::Bool(false)), "no");
+        assert_eq!(formatter.format(&Value::None), "NULL");
+        assert_eq!(formatter.format(&Value::Bytes(vec![0, 255])), "00ff");
+        assert_eq!(formatter.format(&Value::Date(chrono::NaiveDate::from_ymd_opt(2020, 1, 31).unwrap())), "31.01.2020");
+    }
+
+}
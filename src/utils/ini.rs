@@ -0,0 +1,64 @@
+///section of ini file with its key-value pairs, in order of appearance
+#[derive(Clone, Debug, PartialEq)]
+pub struct IniSection {
+    pub name: String,
+    pub values: Vec<(String, String)>,
+}
+
+fn unquote(value: &str) -> &str {
+    if value.len() >= 2 && ((value.starts_with('"') && value.ends_with('"')) || (value.starts_with('\'') && value.ends_with('\''))) {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    }
+}
+
+///parse ini file as used by pg_service.conf and my.cnf. Lines starting with # or ; are comments,
+///!include directives are ignored, keys without value (mysql flags) get empty one
+pub fn parse_ini(content: &str) -> Result<Vec<IniSection>, String> {
+    let mut sections: Vec<IniSection> = vec![];
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') || line.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[') {
+            match name.strip_suffix(']') {
+                Some(name) => sections.push(IniSection { name: name.trim().to_string(), values: vec![] }),
+                None => return Err(format!("line {}: invalid section header: {}", line_number + 1, line)),
+            }
+            continue;
+        }
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), unquote(value.trim())),
+            None => (line, ""),
+        };
+        match sections.last_mut() {
+            Some(section) => section.values.push((key.to_string(), value.to_string())),
+            None => return Err(format!("line {}: value outside of section: {}", line_number + 1, line)),
+        }
+    }
+    Ok(sections)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{parse_ini, IniSection};
+
+    #[test]
+    fn test_parse_ini() {
+        let content = "# comment\n[client]\nuser = joe\npassword=\"p=ss\"\nskip-ssl\n!includedir /etc/mysql/conf.d\n\n[ shop ]\nhost=db\n";
+        assert_eq!(parse_ini(content), Ok(vec![
+            IniSection { name: "client".to_string(), values: vec![
+                ("user".to_string(), "joe".to_string()),
+                ("password".to_string(), "p=ss".to_string()),
+                ("skip-ssl".to_string(), "".to_string()),
+            ]},
+            IniSection { name: "shop".to_string(), values: vec![("host".to_string(), "db".to_string())] },
+        ]));
+        assert!(parse_ini("user=joe").is_err());
+    }
+
+}
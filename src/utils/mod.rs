@@ -1,19 +1,105 @@
+use default_editor;
 use unicode_segmentation::UnicodeSegmentation;
 
+#[cfg(unix)]
+pub mod agent;
+#[cfg(feature = "use_csv")]
+pub mod encoding;
 pub mod fileorstdout;
+pub mod formatter;
+#[cfg(any(feature = "use_mysql", feature = "use_postgres"))]
+pub mod ini;
+#[cfg(feature = "use_mysql")]
+pub mod mycnf;
+pub mod params;
+#[cfg(any(feature = "use_mysql", feature = "use_postgres"))]
+pub mod password;
+#[cfg(feature = "use_postgres")]
+pub mod pg_service;
+#[cfg(feature = "use_postgres")]
+pub mod pgpass;
+pub mod sql;
+#[cfg(any(feature = "use_mysql", feature = "use_postgres"))]
+pub mod ssh;
+pub mod template;
+#[cfg(any(feature = "use_mysql", feature = "use_postgres"))]
+pub mod tls;
+pub mod truncation;
+pub mod url;
+#[cfg(feature = "use_vault")]
+pub mod vault;
 
 
-pub fn truncate_text(text: &str, max_length: u64) -> String {
-    UnicodeSegmentation::graphemes(text, true).take(max_length as usize).collect::<Vec<&str>>().join("")
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TruncateUnit {
+    Graphemes,
+    Chars,
+    Bytes,
 }
 
-pub fn truncate_text_with_note(text: String, truncate: Option<u64>) -> String {
+impl Default for TruncateUnit {
+    fn default() -> TruncateUnit {
+        TruncateUnit::Graphemes
+    }
+}
+
+impl TruncateUnit {
+
+    pub fn from_name(name: &str) -> TruncateUnit {
+        match name {
+            "chars" => TruncateUnit::Chars,
+            "bytes" => TruncateUnit::Bytes,
+            _ => TruncateUnit::Graphemes,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TruncateUnit::Graphemes => "graphemes",
+            TruncateUnit::Chars => "characters",
+            TruncateUnit::Bytes => "bytes",
+        }
+    }
+
+    pub fn length(self, text: &str) -> usize {
+        match self {
+            TruncateUnit::Graphemes => UnicodeSegmentation::graphemes(text, true).count(),
+            TruncateUnit::Chars => text.chars().count(),
+            TruncateUnit::Bytes => text.len(),
+        }
+    }
+}
+
+
+///truncate text to max_length units. When truncating by bytes, text is cut on character boundary.
+pub fn truncate_text(text: &str, max_length: u64, unit: TruncateUnit) -> &str {
+    let end = match unit {
+        TruncateUnit::Graphemes => UnicodeSegmentation::grapheme_indices(text, true)
+            .nth(max_length as usize)
+            .map(|(idx, _)| idx),
+        TruncateUnit::Chars => text.char_indices()
+            .nth(max_length as usize)
+            .map(|(idx, _)| idx),
+        TruncateUnit::Bytes => if text.len() > max_length as usize {
+            Some((0..=max_length as usize).rev().find(|idx| text.is_char_boundary(*idx)).unwrap_or(0))
+        } else {
+            None
+        },
+    };
+    match end {
+        Some(idx) => &text[..idx],
+        None => text,
+    }
+}
+
+pub fn truncate_text_with_note(text: String, truncate: Option<u64>, unit: TruncateUnit) -> String {
     match truncate {
         None => text,
         Some(max_length) => {
-            let truncated_text = truncate_text(&text, max_length);
+            let truncated_text = truncate_text(&text, max_length, unit);
             if truncated_text.len() < text.len() {
-                format!("{} ...({} bytes trimmed)", truncated_text, text.len() -  truncated_text.len())
+                let trimmed = unit.length(&text) - unit.length(truncated_text);
+                format!("{} ...({} {} trimmed)", truncated_text, trimmed, unit.name())
             } else {
                 text
             }
@@ -23,8 +109,27 @@ pub fn truncate_text_with_note(text: String, truncate: Option<u64>) -> String {
 }
 
 
+pub fn query_error_message(query: &str, error: &str) -> String {
+    format!("The following query have failed:\n\n{}\n\nwith error:\n\n{}", query, error)
+}
+
 pub fn report_query_error(query: &str, error: &str) {
-    eprintln!("The following query have failed:\n\n{}\n\nwith error:\n\n{}", query, error)
+    eprintln!("{}", query_error_message(query, error))
+}
+
+///open file in user's text editor and wait until it's closed
+pub fn edit_file(filename: &std::path::Path) -> Result<(), String> {
+    let editor = default_editor::get().map_err(|e| format!("Could not figure out text editor to use: {}", e))?;
+    match std::process::Command::new(&editor).arg(filename).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("{} exited with {}", editor, status)),
+        Err(e) => Err(format!("could not run your text editor ({}): {}", editor, e)),
+    }
+}
+
+///quote table or column name, so that any characters can be used in it
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
 }
 
 
@@ -44,11 +149,19 @@ pub fn escape_binary_data(value: &[u8]) -> String {
 #[cfg(test)]
 mod tests {
 
-    use super::escape_binary_data;
+    use super::{escape_binary_data, truncate_text_with_note, TruncateUnit};
 
     #[test]
     fn test_escape_binary_data() {
         assert_eq!(escape_binary_data(&vec!['a' as u8, 0x0, 'b' as u8, 0x9 ]), "a\\x00b\\t");
     }
 
+    #[test]
+    fn test_truncate_text_with_note() {
+        let text = "żółw".to_string();
+        assert_eq!(truncate_text_with_note(text.clone(), Some(2), TruncateUnit::Graphemes), "żó ...(2 graphemes trimmed)");
+        assert_eq!(truncate_text_with_note(text.clone(), Some(3), TruncateUnit::Bytes), "ż ...(5 bytes trimmed)");
+        assert_eq!(truncate_text_with_note(text, Some(4), TruncateUnit::Chars), "żółw");
+    }
+
 }
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use dirs::home_dir;
+
+use crate::utils::ini::{parse_ini, IniSection};
+
+
+///option files read by mysql client, later ones override earlier
+pub fn get_mycnf_paths() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from("/etc/my.cnf"), PathBuf::from("/etc/mysql/my.cnf")];
+    if let Some(home) = home_dir() {
+        paths.push(home.join(".my.cnf"));
+    }
+    paths
+}
+
+///groups from given option files, in order
+pub fn read_mycnf(paths: &[PathBuf]) -> Result<Vec<IniSection>, String> {
+    let mut groups = vec![];
+    for path in paths.iter().filter(|path| path.is_file()) {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+        groups.extend(parse_ini(&content).map_err(|e| format!("{}: {}", path.display(), e))?);
+    }
+    Ok(groups)
+}
+
+///options from groups with given names, later values override earlier ones
+pub fn group_options(groups: &[IniSection], names: &[&str]) -> Vec<(String, String)> {
+    let mut options: Vec<(String, String)> = vec![];
+    for group in groups.iter().filter(|group| names.contains(&group.name.as_str())) {
+        for (key, value) in group.values.iter() {
+            let key = key.replace('_', "-");
+            options.retain(|(existing, _)| *existing != key);
+            options.push((key, value.clone()));
+        }
+    }
+    options
+}
+
+///password from [client] and [mysql] groups, the way mysql client uses it. It's used only when
+///user isn't given or matches one from option files
+pub fn find_password(user: &Option<String>) -> Option<String> {
+    let groups = read_mycnf(&get_mycnf_paths()).ok()?;
+    let options = group_options(&groups, &["client", "mysql"]);
+    let get = |name: &str| options.iter().find(|(key, _)| key == name).map(|(_, value)| value.clone());
+    match (user, get("user")) {
+        (Some(user), Some(mycnf_user)) if *user != mycnf_user => None,
+        _ => get("password"),
+    }
+}
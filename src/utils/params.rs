@@ -0,0 +1,153 @@
+use crate::utils::sql::{comment_end, quoted_end, Dialect};
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    Null,
+}
+
+impl ParamValue {
+    ///sql type name, used by databases that need placeholder type. Strings and nulls have none,
+    ///since they can stand for dates, numbers and so on, so their type is inferred by database
+    pub fn type_name(&self) -> Option<&'static str> {
+        match self {
+            ParamValue::Int(_) => Some("int8"),
+            ParamValue::Float(_) => Some("float8"),
+            ParamValue::Bool(_) => Some("bool"),
+            ParamValue::String(_) | ParamValue::Null => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryParam {
+    pub name: String,
+    pub value: ParamValue,
+}
+
+///parse name[:type]=value, where type is int, float, bool, string (default) or null
+pub fn parse_query_param(text: &str) -> Result<QueryParam, String> {
+    let mut parts = text.splitn(2, '=');
+    let name_and_type = parts.next().unwrap();
+    let value = match parts.next() {
+        Some(value) => value,
+        None => return Err(format!("expected name[:type]=value, got: {}", text)),
+    };
+    let mut name_parts = name_and_type.splitn(2, ':');
+    let name = name_parts.next().unwrap();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("invalid parameter name: {} (use letters, digits and _)", name));
+    }
+    let value = match name_parts.next().unwrap_or("string") {
+        "int" => value.parse().map(ParamValue::Int).map_err(|e| format!("invalid int value for {}: {}", name, e))?,
+        "float" => value.parse().map(ParamValue::Float).map_err(|e| format!("invalid float value for {}: {}", name, e))?,
+        "bool" => match value.to_lowercase().as_str() {
+            "true" | "1" => ParamValue::Bool(true),
+            "false" | "0" => ParamValue::Bool(false),
+            _ => return Err(format!("invalid bool value for {}: {} (expected true or false)", name, value)),
+        },
+        "string" => ParamValue::String(value.to_string()),
+        "null" => ParamValue::Null,
+        type_name => return Err(format!("unknown parameter type: {} (expected int, float, bool, string or null)", type_name)),
+    };
+    Ok(QueryParam { name: name.to_string(), value })
+}
+
+///replace :name placeholders with ones generated by placeholder function (given 1-based position and parameter).
+///Returns query and parameters in order they should be bound. If numbered is true,
+///repeated placeholders use the same position, otherwise every placeholder is bound separately.
+///Strings, quoted identifiers, comments and :: casts are left untouched.
+///Without params query is returned as it is, so that e.g. postgres array slices (arr[1:n]) keep working
+pub fn bind_params<'a>(
+    query: &str,
+    params: &'a [QueryParam],
+    dialect: Dialect,
+    numbered: bool,
+    placeholder: impl Fn(usize, &QueryParam) -> String,
+) -> Result<(String, Vec<&'a QueryParam>), String> {
+    if params.is_empty() {
+        return Ok((query.to_string(), vec![]));
+    }
+    let chars: Vec<char> = query.chars().collect();
+    let mut result = String::with_capacity(query.len());
+    let mut bound: Vec<&QueryParam> = vec![];
+    let mut idx = 0;
+    while idx < chars.len() {
+        let end = match (chars[idx], chars.get(idx + 1).copied()) {
+            (':', Some(':')) => idx + 2,
+            (':', Some(n)) if n.is_ascii_alphabetic() || n == '_' => {
+                let name_end = chars[idx + 1..]
+                    .iter()
+                    .position(|&n| !(n.is_ascii_alphanumeric() || n == '_'))
+                    .map(|pos| idx + 1 + pos)
+                    .unwrap_or(chars.len());
+                let name: String = chars[idx + 1..name_end].iter().collect();
+                let param = match params.iter().rev().find(|param| param.name == name) {
+                    Some(param) => param,
+                    None => return Err(format!("no value for parameter :{}, use --param {}=value", name, name)),
+                };
+                let position = match bound.iter().position(|bound_param| numbered && bound_param.name == name) {
+                    Some(position) => position + 1,
+                    None => {
+                        bound.push(param);
+                        bound.len()
+                    }
+                };
+                result.push_str(&placeholder(position, param));
+                idx = name_end;
+                continue;
+            },
+            //quoted text and comments are copied as they are
+            _ => comment_end(&chars, idx, dialect)
+                .or_else(|| quoted_end(&chars, idx, dialect))
+                .unwrap_or(idx + 1),
+        };
+        result.extend(&chars[idx..end]);
+        idx = end;
+    }
+    Ok((result, bound))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{bind_params, parse_query_param, ParamValue, QueryParam};
+    use crate::utils::sql::Dialect;
+
+    #[test]
+    fn test_parse_query_param() {
+        assert_eq!(parse_query_param("id:int=5"), Ok(QueryParam { name: "id".to_string(), value: ParamValue::Int(5) }));
+        assert_eq!(parse_query_param("name=a=b"), Ok(QueryParam { name: "name".to_string(), value: ParamValue::String("a=b".to_string()) }));
+        assert_eq!(parse_query_param("x:null="), Ok(QueryParam { name: "x".to_string(), value: ParamValue::Null }));
+        assert!(parse_query_param("id:int=x").is_err());
+        assert!(parse_query_param("id:date=2020-01-01").is_err());
+        assert!(parse_query_param("id").is_err());
+    }
+
+    #[test]
+    fn test_bind_params() {
+        let params = vec![parse_query_param("a:int=1").unwrap(), parse_query_param("b=x").unwrap()];
+        let query = "select ':a', \":a\", x::text -- :c\n from t where a = :a and b = :b or a > :a /* :c */";
+        let placeholder = |position, param: &QueryParam| match param.value.type_name() {
+            Some(type_name) => format!("${}::{}", position, type_name),
+            None => format!("${}", position),
+        };
+        let (sql, bound) = bind_params(query, &params, Dialect::Postgres, true, placeholder).unwrap();
+        assert_eq!(sql, "select ':a', \":a\", x::text -- :c\n from t where a = $1::int8 and b = $2 or a > $1::int8 /* :c */");
+        assert_eq!(bound, vec![&params[0], &params[1]]);
+        let (sql, bound) = bind_params(query, &params, Dialect::Postgres, false, |_, _| "?".to_string()).unwrap();
+        assert!(sql.ends_with("where a = ? and b = ? or a > ? /* :c */"));
+        assert_eq!(bound, vec![&params[0], &params[1], &params[0]]);
+        let (sql, _) = bind_params("select $$:a$$, $x$ :a $x$, :b", &params, Dialect::Postgres, true, |position, _| format!("${}", position)).unwrap();
+        assert_eq!(sql, "select $$:a$$, $x$ :a $x$, $1");
+        assert!(bind_params("select :missing", &params, Dialect::Mysql, false, |_, _| "?".to_string()).is_err());
+        let query = "select arr[1:n], :a from t";
+        assert_eq!(bind_params(query, &[], Dialect::Postgres, true, |position, _| format!("${}", position)), Ok((query.to_string(), vec![])));
+    }
+
+}
@@ -0,0 +1,135 @@
+use std::process::{Command, Stdio};
+
+use atty;
+use rpassword;
+use serde_derive::{Deserialize, Serialize};
+
+#[cfg(unix)]
+use crate::utils::agent;
+#[cfg(feature = "use_vault")]
+use crate::utils::vault;
+
+
+//ways to get password other than storing it in source config (not a doc comment, since structopt
+//would use it as description of subcommands this is flattened into)
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, StructOpt)]
+pub struct PasswordOptions {
+    #[structopt(long = "password-command", help = "command printing password (first line of output is used), e.g. \"pass show db/prod\"")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_command: Option<String>,
+    #[structopt(long = "password-env", help = "environment variable containing password")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_env: Option<String>,
+    #[structopt(long = "password-vault", help = "name of password stored in dbfish vault (see: dbfish vault set)")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_vault: Option<String>,
+}
+
+fn run_password_command(command: &str) -> Result<String, String> {
+    #[cfg(windows)]
+    let mut shell = Command::new("cmd");
+    #[cfg(windows)]
+    shell.arg("/C");
+    #[cfg(not(windows))]
+    let mut shell = Command::new("sh");
+    #[cfg(not(windows))]
+    shell.arg("-c");
+    let output = shell
+        .arg(command)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| format!("can't run password command {}: {}", command, e))?;
+    if !output.status.success() {
+        return Err(format!("password command {} failed: {}", command, output.status));
+    }
+    let output = String::from_utf8(output.stdout).map_err(|_| format!("password command {} printed invalid utf-8", command))?;
+    Ok(output.lines().next().unwrap_or("").to_string())
+}
+
+///password can be asked for only when user is at terminal
+pub fn can_prompt() -> bool {
+    atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stderr)
+}
+
+///ask for password on terminal, unless password agent (see: dbfish agent start) has one cached under cache_key
+pub fn ask_password(cache_key: &str, use_cache: bool) -> Result<String, String> {
+    #[cfg(unix)]
+    if use_cache {
+        if let Some(password) = agent::get(cache_key) {
+            return Ok(password);
+        }
+    }
+    rpassword::prompt_password(format!("Password for {}: ", cache_key)).map_err(|e| format!("can't read password: {}", e))
+}
+
+///cache password that was asked for, if password agent is running
+pub fn remember_password(cache_key: &str, password: &str) {
+    #[cfg(unix)]
+    agent::set(cache_key, password);
+}
+
+///identifies password in agent cache, e.g. postgres://joe@localhost:5432/shop
+pub fn password_cache_key(scheme: &str, user: &Option<String>, host: &Option<String>, port: &Option<u16>, database: &Option<String>) -> String {
+    format!(
+        "{}://{}@{}{}/{}",
+        scheme,
+        user.as_deref().unwrap_or(""),
+        host.as_deref().unwrap_or("localhost"),
+        port.map(|port| format!(":{}", port)).unwrap_or_default(),
+        database.as_deref().unwrap_or(""),
+    )
+}
+
+impl PasswordOptions {
+
+    pub fn is_empty(&self) -> bool {
+        self.password_command.is_none() && self.password_env.is_none() && self.password_vault.is_none()
+    }
+
+    ///password given directly (asked for when --password has no value), or from environment variable,
+    ///command or vault (in that order)
+    pub fn resolve(&self, password: &Option<Option<String>>, cache_key: &str) -> Result<Option<String>, String> {
+        match password {
+            Some(Some(password)) => return Ok(Some(password.clone())),
+            Some(None) => return ask_password(cache_key, true).map(Some),
+            None => {},
+        }
+        if let Some(name) = &self.password_env {
+            return match std::env::var(name) {
+                Ok(value) => Ok(Some(value)),
+                Err(_) => Err(format!("password environment variable {} is not set", name)),
+            };
+        }
+        if let Some(command) = &self.password_command {
+            return run_password_command(command).map(Some);
+        }
+        if let Some(name) = &self.password_vault {
+            #[cfg(feature = "use_vault")]
+            return vault::get_secret(name).map(Some);
+            #[cfg(not(feature = "use_vault"))]
+            return Err(format!("can't read password {} from vault, dbfish was compiled without vault support", name));
+        }
+        Ok(None)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{password_cache_key, PasswordOptions};
+
+    #[test]
+    fn test_resolve_password() {
+        let mut options = PasswordOptions { password_command: Some("echo secret; echo other".to_string()), ..Default::default() };
+        assert_eq!(options.resolve(&Some(Some("given".to_string())), "test"), Ok(Some("given".to_string())));
+        #[cfg(unix)]
+        assert_eq!(options.resolve(&None, "test"), Ok(Some("secret".to_string())));
+        options.password_env = Some("DBFISH_TEST_PASSWORD_UNSET".to_string());
+        assert!(options.resolve(&None, "test").is_err());
+        assert_eq!(PasswordOptions::default().resolve(&None, "test"), Ok(None));
+        assert_eq!(password_cache_key("postgres", &Some("joe".to_string()), &None, &Some(5433), &Some("shop".to_string())), "postgres://joe@localhost:5433/shop");
+    }
+
+}
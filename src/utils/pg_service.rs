@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use dirs::home_dir;
+
+use crate::utils::ini::{parse_ini, IniSection};
+
+
+///service files in order of precedence: PGSERVICEFILE or ~/.pg_service.conf, then PGSYSCONFDIR/pg_service.conf
+pub fn get_pg_service_paths() -> Vec<PathBuf> {
+    let mut paths = vec![];
+    match std::env::var_os("PGSERVICEFILE") {
+        Some(path) => paths.push(PathBuf::from(path)),
+        None => if let Some(home) = home_dir() {
+            paths.push(home.join(".pg_service.conf"));
+        },
+    }
+    if let Some(sysconfdir) = std::env::var_os("PGSYSCONFDIR") {
+        paths.push(PathBuf::from(sysconfdir).join("pg_service.conf"));
+    }
+    paths
+}
+
+///services defined in given files, first definition of service wins
+pub fn read_pg_services(paths: &[PathBuf]) -> Result<Vec<IniSection>, String> {
+    let mut services: Vec<IniSection> = vec![];
+    for path in paths.iter().filter(|path| path.is_file()) {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+        for section in parse_ini(&content).map_err(|e| format!("{}: {}", path.display(), e))? {
+            if !services.iter().any(|service| service.name == section.name) {
+                services.push(section);
+            }
+        }
+    }
+    Ok(services)
+}
@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+use dirs::home_dir;
+
+
+///line of ~/.pgpass, * matches any value
+#[derive(Clone, Debug, PartialEq)]
+pub struct PgpassEntry {
+    pub host: String,
+    pub port: String,
+    pub database: String,
+    pub user: String,
+    pub password: String,
+}
+
+///PGPASSFILE or ~/.pgpass
+pub fn get_pgpass_path() -> Option<PathBuf> {
+    match std::env::var_os("PGPASSFILE") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => home_dir().map(|home| home.join(".pgpass")),
+    }
+}
+
+///split line on unescaped colons, \: and \\ are escapes
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => if let Some(escaped) = chars.next() {
+                fields.last_mut().unwrap().push(escaped);
+            },
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+///parse hostname:port:database:username:password lines, skipping comments and invalid lines
+pub fn parse_pgpass(content: &str) -> Vec<PgpassEntry> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = split_fields(line);
+            if fields.len() != 5 {
+                return None;
+            }
+            let password = fields.pop().unwrap();
+            let user = fields.pop().unwrap();
+            let database = fields.pop().unwrap();
+            let port = fields.pop().unwrap();
+            let host = fields.pop().unwrap();
+            Some(PgpassEntry { host, port, database, user, password })
+        })
+        .collect()
+}
+
+///read pgpass file. Like libpq, file accessible by group or others is ignored
+pub fn read_pgpass() -> Vec<PgpassEntry> {
+    let path = match get_pgpass_path() {
+        Some(path) if path.is_file() => path,
+        _ => return vec![],
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            if metadata.permissions().mode() & 0o077 != 0 {
+                eprintln!("Warning: password file {} has group or world access; permissions should be u=rw (0600) or less", path.display());
+                return vec![];
+            }
+        }
+    }
+    match std::fs::read_to_string(&path) {
+        Ok(content) => parse_pgpass(&content),
+        Err(_) => vec![],
+    }
+}
+
+fn field_matches(pattern: &str, value: &str) -> bool {
+    pattern == "*" || pattern == value
+}
+
+impl PgpassEntry {
+    pub fn matches(&self, host: &str, port: &str, database: &str, user: &str) -> bool {
+        field_matches(&self.host, host) && field_matches(&self.port, port) && field_matches(&self.database, database) && field_matches(&self.user, user)
+    }
+}
+
+///find password for connection the way libpq does: host defaults to localhost (also used for unix sockets),
+///port to 5432 and database to user name
+pub fn find_password(entries: &[PgpassEntry], host: &Option<String>, port: &Option<u16>, database: &Option<String>, user: &Option<String>) -> Option<String> {
+    let user = user.as_deref()?;
+    let host = match host.as_deref() {
+        None | Some("") => "localhost",
+        Some(host) if host.starts_with('/') => "localhost",
+        Some(host) => host,
+    };
+    let port = port.unwrap_or(5432).to_string();
+    let database = database.as_deref().unwrap_or(user);
+    entries.iter().find(|entry| entry.matches(host, &port, database, user)).map(|entry| entry.password.clone())
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{find_password, parse_pgpass};
+
+    #[test]
+    fn test_pgpass() {
+        let entries = parse_pgpass("# comment\ndb.example.com:5432:shop:joe:s3cr\\:t\n*:*:*:joe:fallback\ninvalid line\n");
+        assert_eq!(entries.len(), 2);
+        let joe = Some("joe".to_string());
+        assert_eq!(find_password(&entries, &Some("db.example.com".to_string()), &None, &Some("shop".to_string()), &joe), Some("s3cr:t".to_string()));
+        assert_eq!(find_password(&entries, &Some("db.example.com".to_string()), &Some(6432), &Some("shop".to_string()), &joe), Some("fallback".to_string()));
+        assert_eq!(find_password(&entries, &None, &None, &None, &Some("ann".to_string())), None);
+    }
+
+}
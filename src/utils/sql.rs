@@ -0,0 +1,118 @@
+///sql syntax differences that matter when scanning queries
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dialect {
+    ///backslash escapes in strings, # comments and `quoted` identifiers
+    Mysql,
+    ///$tag$ quoted strings. Also used for DuckDB
+    Postgres,
+    ///`quoted` identifiers
+    Sqlite,
+}
+
+///if string, quoted identifier or dollar quoted string starts at idx, return position after its end
+pub fn quoted_end(chars: &[char], idx: usize, dialect: Dialect) -> Option<usize> {
+    let c = chars[idx];
+    match c {
+        '\'' | '"' | '`' if c != '`' || dialect != Dialect::Postgres => {
+            let mut pos = idx + 1;
+            while pos < chars.len() {
+                match chars[pos] {
+                    '\\' if dialect == Dialect::Mysql && c != '`' => pos += 2,
+                    q if q == c => return Some(pos + 1),
+                    _ => pos += 1,
+                }
+            }
+            Some(chars.len())
+        },
+        //$$text$$ or $tag$text$tag$
+        '$' if dialect == Dialect::Postgres => {
+            let tag_end = chars[idx + 1..].iter().position(|&n| !(n.is_ascii_alphanumeric() || n == '_'))? + idx + 1;
+            let starts_as_tag = chars.get(idx + 1).map_or(false, |&n| n == '$' || n.is_ascii_alphabetic() || n == '_');
+            if !starts_as_tag || chars[tag_end] != '$' {
+                return None;
+            }
+            let tag = &chars[idx..=tag_end];
+            Some(
+                chars[tag_end + 1..]
+                    .windows(tag.len())
+                    .position(|w| w == tag)
+                    .map(|pos| tag_end + 1 + pos + tag.len())
+                    .unwrap_or(chars.len())
+            )
+        },
+        _ => None,
+    }
+}
+
+///if comment starts at idx, return position after its end
+pub fn comment_end(chars: &[char], idx: usize, dialect: Dialect) -> Option<usize> {
+    match (chars[idx], chars.get(idx + 1)) {
+        ('-', Some('-')) => Some(chars[idx..].iter().position(|&c| c == '\n').map(|pos| idx + pos + 1).unwrap_or(chars.len())),
+        ('#', _) if dialect == Dialect::Mysql => Some(chars[idx..].iter().position(|&c| c == '\n').map(|pos| idx + pos + 1).unwrap_or(chars.len())),
+        ('/', Some('*')) => Some(chars[idx + 2..].windows(2).position(|w| w == ['*', '/']).map(|pos| idx + pos + 4).unwrap_or(chars.len())),
+        _ => None,
+    }
+}
+
+///split sql into statements on ; outside of strings and comments.
+///Statements containing only comments are skipped.
+pub fn split_statements(sql: &str, dialect: Dialect) -> Vec<String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut statements = vec![];
+    let mut start = 0;
+    let mut has_content = false;
+    let mut idx = 0;
+    while idx <= chars.len() {
+        if idx == chars.len() || chars[idx] == ';' {
+            if has_content {
+                statements.push(chars[start..idx].iter().collect::<String>().trim().to_string());
+            }
+            start = idx + 1;
+            has_content = false;
+            idx += 1;
+        } else if let Some(end) = comment_end(&chars, idx, dialect) {
+            idx = end;
+        } else if let Some(end) = quoted_end(&chars, idx, dialect) {
+            has_content = true;
+            idx = end;
+        } else {
+            has_content = has_content || !chars[idx].is_whitespace();
+            idx += 1;
+        }
+    }
+    statements
+}
+
+///split query into setup statements and the last one, which returns results
+pub fn split_setup(query: &str, dialect: Dialect) -> (Vec<String>, String) {
+    let mut statements = split_statements(query, dialect);
+    match statements.pop() {
+        Some(last) => (statements, last),
+        None => (vec![], query.to_string()),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{split_statements, Dialect};
+
+    #[test]
+    fn test_split_statements() {
+        let sql = "set @a = 'x;\\'y';\n-- comment; here\ncreate temporary table t as select 1 `a;b` /* ; */;\n# done;\nselect * from t;\n-- end\n";
+        assert_eq!(split_statements(sql, Dialect::Mysql), vec![
+            "set @a = 'x;\\'y'",
+            "-- comment; here\ncreate temporary table t as select 1 `a;b` /* ; */",
+            "# done;\nselect * from t",
+        ]);
+        let sql = "create function f() returns int as $body$ begin; return 1; end $body$ language plpgsql; select 'it''s;', f()";
+        assert_eq!(split_statements(sql, Dialect::Postgres), vec![
+            "create function f() returns int as $body$ begin; return 1; end $body$ language plpgsql",
+            "select 'it''s;', f()",
+        ]);
+        assert_eq!(split_statements("select '\\'; select 2", Dialect::Sqlite), vec!["select '\\'", "select 2"]);
+        assert_eq!(split_statements(" ;; -- nothing", Dialect::Sqlite), Vec::<String>::new());
+    }
+
+}
@@ -0,0 +1,338 @@
+use serde_derive::{Deserialize, Serialize};
+
+#[cfg(feature = "use_ssh")]
+use std::io::{ErrorKind, Read, Write};
+#[cfg(feature = "use_ssh")]
+use std::net::{Shutdown, TcpListener, TcpStream};
+#[cfg(feature = "use_ssh")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "use_ssh")]
+use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+#[cfg(feature = "use_ssh")]
+use std::thread::JoinHandle;
+#[cfg(feature = "use_ssh")]
+use std::time::Duration;
+
+#[cfg(feature = "use_ssh")]
+use dirs::home_dir;
+#[cfg(feature = "use_ssh")]
+use rpassword;
+#[cfg(feature = "use_ssh")]
+use ssh2::{Channel, CheckResult, ErrorCode, KnownHostFileKind, Session};
+
+#[cfg(feature = "use_ssh")]
+use crate::utils::password::can_prompt;
+
+
+//bastion host database is reached through (not a doc comment, see PasswordOptions)
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, StructOpt)]
+pub struct SshOptions {
+    #[structopt(long = "ssh-host", help = "connect through ssh tunnel via this host")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh_host: Option<String>,
+    #[structopt(long = "ssh-user", help = "ssh username (default: current user)")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh_user: Option<String>,
+    #[structopt(long = "ssh-key", help = "ssh private key (default: ssh agent, then ~/.ssh/id_ed25519, id_ecdsa, id_rsa)")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh_key: Option<String>,
+    #[structopt(long = "ssh-port", help = "ssh port (default: 22)")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh_port: Option<u16>,
+}
+
+impl SshOptions {
+
+    pub fn is_enabled(&self) -> bool {
+        self.ssh_host.is_some()
+    }
+
+    //fill values not given on command line
+    pub fn update_from(&mut self, other: &SshOptions) {
+        if self.ssh_host.is_none() {
+            self.ssh_host = other.ssh_host.clone();
+        }
+        if self.ssh_user.is_none() {
+            self.ssh_user = other.ssh_user.clone();
+        }
+        if self.ssh_key.is_none() {
+            self.ssh_key = other.ssh_key.clone();
+        }
+        if self.ssh_port.is_none() {
+            self.ssh_port = other.ssh_port;
+        }
+    }
+}
+
+///where database listens, as seen from ssh host
+#[derive(Clone, Debug, PartialEq)]
+pub enum TunnelTarget {
+    Tcp(String, u16),
+    Socket(String),
+}
+
+///local port forwarded to database through ssh, closed when dropped
+#[cfg(feature = "use_ssh")]
+pub struct SshTunnel {
+    pub local_port: u16,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[cfg(not(feature = "use_ssh"))]
+pub struct SshTunnel {
+    pub local_port: u16,
+}
+
+#[cfg(feature = "use_ssh")]
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(feature = "use_ssh")]
+fn ssh_error(action: &str, e: ssh2::Error) -> String {
+    format!("ssh: can't {}: {}", action, e)
+}
+
+#[cfg(feature = "use_ssh")]
+fn check_host_key(session: &Session, host: &str, port: u16) -> Result<(), String> {
+    let mut known_hosts = session.known_hosts().map_err(|e| ssh_error("read known hosts", e))?;
+    let known_hosts_path = home_dir().map(|home| home.join(".ssh").join("known_hosts"));
+    if let Some(path) = known_hosts_path.filter(|path| path.is_file()) {
+        known_hosts.read_file(&path, KnownHostFileKind::OpenSSH).map_err(|e| ssh_error(&format!("read {}", path.display()), e))?;
+    }
+    let (key, _) = session.host_key().ok_or_else(|| format!("ssh: host {} didn't send host key", host))?;
+    match known_hosts.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::NotFound => Err(format!("ssh: host key of {} is not known, connect to it with ssh once to add it to ~/.ssh/known_hosts", host)),
+        CheckResult::Mismatch => Err(format!("ssh: host key of {} doesn't match one in ~/.ssh/known_hosts, someone may be intercepting the connection", host)),
+        CheckResult::Failure => Err(format!("ssh: can't check host key of {}", host)),
+    }
+}
+
+#[cfg(feature = "use_ssh")]
+fn authenticate_with_key(session: &Session, user: &str, key: &Path, ask_passphrase: bool) -> bool {
+    if session.userauth_pubkey_file(user, None, key, None).is_ok() {
+        return true;
+    }
+    if ask_passphrase && can_prompt() {
+        if let Ok(passphrase) = rpassword::prompt_password(format!("Passphrase for {}: ", key.display())) {
+            return session.userauth_pubkey_file(user, None, key, Some(&passphrase)).is_ok();
+        }
+    }
+    false
+}
+
+///given key, or ssh agent and default keys
+#[cfg(feature = "use_ssh")]
+fn authenticate(session: &Session, user: &str, ssh_key: &Option<String>) -> bool {
+    if let Some(key) = ssh_key {
+        return authenticate_with_key(session, user, Path::new(key), true);
+    }
+    if session.userauth_agent(user).is_ok() {
+        return true;
+    }
+    let default_keys: Vec<PathBuf> = match home_dir() {
+        Some(home) => ["id_ed25519", "id_ecdsa", "id_rsa"].iter().map(|name| home.join(".ssh").join(name)).collect(),
+        None => vec![],
+    };
+    default_keys.iter().filter(|key| key.is_file()).any(|key| authenticate_with_key(session, user, key, false))
+}
+
+#[cfg(feature = "use_ssh")]
+fn connect_session(ssh_options: &SshOptions, host: &str, timeout: &Option<u64>) -> Result<Session, String> {
+    let port = ssh_options.ssh_port.unwrap_or(22);
+    let tcp = TcpStream::connect((host, port)).map_err(|e| format!("ssh: can't connect to {}:{}: {}", host, port, e))?;
+    let mut session = Session::new().map_err(|e| ssh_error("start session", e))?;
+    if let Some(timeout) = timeout {
+        session.set_timeout((*timeout * 1000) as u32);
+    }
+    session.set_tcp_stream(tcp);
+    session.handshake().map_err(|e| ssh_error(&format!("connect to {}", host), e))?;
+    check_host_key(&session, host, port)?;
+    let user = match &ssh_options.ssh_user {
+        Some(user) => user.clone(),
+        None => std::env::var("USER").or_else(|_| std::env::var("USERNAME")).map_err(|_| "ssh: can't determine user name, give it with --ssh-user".to_string())?,
+    };
+    if !authenticate(&session, &user, &ssh_options.ssh_key) {
+        return Err(format!("ssh: authentication of {}@{} failed", user, host));
+    }
+    session.set_timeout(0);
+    Ok(session)
+}
+
+///LIBSSH2_ERROR_EAGAIN, returned by non-blocking session when operation has to be retried
+#[cfg(feature = "use_ssh")]
+const SSH_WOULD_BLOCK: ErrorCode = ErrorCode::Session(-37);
+
+#[cfg(feature = "use_ssh")]
+fn is_would_block(e: &std::io::Error) -> bool {
+    e.kind() == ErrorKind::WouldBlock
+}
+
+///single connection forwarded through ssh channel, with data waiting to be written in each direction
+#[cfg(feature = "use_ssh")]
+struct Forward {
+    local: TcpStream,
+    channel: Channel,
+    to_remote: Vec<u8>,
+    to_local: Vec<u8>,
+    local_eof: bool,
+    eof_sent: bool,
+}
+
+#[cfg(feature = "use_ssh")]
+impl Forward {
+
+    ///move data both ways, returns false once connection is finished
+    fn pump(&mut self, buffer: &mut [u8], progress: &mut bool) -> bool {
+        if !self.local_eof && self.to_remote.is_empty() {
+            match self.local.read(buffer) {
+                Ok(0) => { self.local_eof = true; *progress = true; },
+                Ok(n) => { self.to_remote.extend_from_slice(&buffer[..n]); *progress = true; },
+                Err(ref e) if is_would_block(e) => {},
+                Err(_) => return false,
+            }
+        }
+        if !self.to_remote.is_empty() {
+            match self.channel.write(&self.to_remote) {
+                Ok(n) => { self.to_remote.drain(..n); *progress = true; },
+                Err(ref e) if is_would_block(e) => {},
+                Err(_) => return false,
+            }
+        }
+        if self.local_eof && self.to_remote.is_empty() && !self.eof_sent {
+            match self.channel.send_eof() {
+                Ok(()) => self.eof_sent = true,
+                Err(ref e) if e.code() == SSH_WOULD_BLOCK => {},
+                Err(_) => return false,
+            }
+        }
+        if self.to_local.is_empty() {
+            match self.channel.read(buffer) {
+                Ok(0) => if self.channel.eof() {
+                    //database closed connection
+                    return false;
+                },
+                Ok(n) => { self.to_local.extend_from_slice(&buffer[..n]); *progress = true; },
+                Err(ref e) if is_would_block(e) => {},
+                Err(_) => return false,
+            }
+        }
+        if !self.to_local.is_empty() {
+            match self.local.write(&self.to_local) {
+                Ok(n) => { self.to_local.drain(..n); *progress = true; },
+                Err(ref e) if is_would_block(e) => {},
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+}
+
+#[cfg(feature = "use_ssh")]
+fn open_channel(session: &Session, target: &TunnelTarget) -> Result<Channel, ssh2::Error> {
+    match target {
+        TunnelTarget::Tcp(host, port) => session.channel_direct_tcpip(host, *port, None),
+        TunnelTarget::Socket(path) => session.channel_direct_streamlocal(path, None),
+    }
+}
+
+///accept local connections and forward them through ssh until stopped. Session is non-blocking,
+///so that one thread can serve all connections
+#[cfg(feature = "use_ssh")]
+fn forward_connections(session: Session, listener: TcpListener, target: TunnelTarget, stop: Arc<AtomicBool>) {
+    session.set_blocking(false);
+    let mut waiting: Vec<TcpStream> = vec![];
+    let mut forwards: Vec<Forward> = vec![];
+    let mut buffer = vec![0u8; 32768];
+    while !stop.load(Ordering::Relaxed) {
+        let mut progress = false;
+        if let Ok((stream, _)) = listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                waiting.push(stream);
+                progress = true;
+            }
+        }
+        if !waiting.is_empty() {
+            match open_channel(&session, &target) {
+                Ok(channel) => {
+                    let local = waiting.remove(0);
+                    forwards.push(Forward { local, channel, to_remote: vec![], to_local: vec![], local_eof: false, eof_sent: false });
+                    progress = true;
+                },
+                Err(ref e) if e.code() == SSH_WOULD_BLOCK => {},
+                Err(e) => {
+                    eprintln!("Error: ssh: can't open tunnel to {:?}: {}", target, e);
+                    let _ = waiting.remove(0).shutdown(Shutdown::Both);
+                },
+            }
+        }
+        forwards.retain_mut(|forward| forward.pump(&mut buffer, &mut progress));
+        if !progress {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+///connect to ssh host and forward local port to target
+#[cfg(feature = "use_ssh")]
+pub fn open_tunnel(ssh_options: &SshOptions, target: TunnelTarget, timeout: &Option<u64>) -> Result<SshTunnel, String> {
+    let host = match &ssh_options.ssh_host {
+        Some(host) => host,
+        None => return Err("ssh: no --ssh-host given".to_string()),
+    };
+    let session = connect_session(ssh_options, host, timeout)?;
+    let listener = TcpListener::bind(("127.0.0.1", 0)).map_err(|e| format!("ssh: can't listen on local port: {}", e))?;
+    listener.set_nonblocking(true).map_err(|e| format!("ssh: can't listen on local port: {}", e))?;
+    let local_port = listener.local_addr().map_err(|e| format!("ssh: can't listen on local port: {}", e))?.port();
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = stop.clone();
+    let thread = std::thread::spawn(move || forward_connections(session, listener, target, thread_stop));
+    Ok(SshTunnel { local_port, stop, thread: Some(thread) })
+}
+
+#[cfg(not(feature = "use_ssh"))]
+pub fn open_tunnel(_ssh_options: &SshOptions, _target: TunnelTarget, _timeout: &Option<u64>) -> Result<SshTunnel, String> {
+    Err("can't use ssh tunnel, dbfish was compiled without ssh support".to_string())
+}
+
+///open tunnel if ssh host is set
+pub fn open_tunnel_if_enabled(ssh_options: &SshOptions, target: TunnelTarget, timeout: &Option<u64>) -> Result<Option<SshTunnel>, String> {
+    if !ssh_options.is_enabled() {
+        return Ok(None);
+    }
+    open_tunnel(ssh_options, target, timeout).map(Some)
+}
+
+///open tunnel if ssh host is set, exiting with error message if that fails
+pub fn open_tunnel_or_exit(ssh_options: &SshOptions, target: TunnelTarget, timeout: &Option<u64>) -> Option<SshTunnel> {
+    match open_tunnel_if_enabled(ssh_options, target, timeout) {
+        Ok(tunnel) => tunnel,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::SshOptions;
+
+    #[test]
+    fn test_update_ssh_options() {
+        let mut options = SshOptions { ssh_user: Some("cli".to_string()), ..Default::default() };
+        options.update_from(&SshOptions { ssh_host: Some("bastion".to_string()), ssh_user: Some("saved".to_string()), ssh_key: None, ssh_port: Some(2222) });
+        assert_eq!(options, SshOptions { ssh_host: Some("bastion".to_string()), ssh_user: Some("cli".to_string()), ssh_key: None, ssh_port: Some(2222) });
+        assert!(options.is_enabled() && !SshOptions::default().is_enabled());
+    }
+
+}
@@ -0,0 +1,202 @@
+use std::convert::TryFrom;
+
+use chrono::{self, Datelike, NaiveDate, NaiveDateTime};
+use chrono::format::{Item, StrftimeItems};
+
+
+#[derive(Debug, PartialEq)]
+enum TemplateValue {
+    Text(String),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+///check format of --var value
+pub fn validate_var(var: String) -> Result<(), String> {
+    match var.split_once('=') {
+        Some((name, _)) if is_identifier(name) => Ok(()),
+        _ => Err(format!("expected name=value, got: {}", var)),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => chars.all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        _ => false,
+    }
+}
+
+///find variable: given name=value pairs are searched first (first match wins), then environment
+fn lookup_var(name: &str, vars: &[String]) -> Option<String> {
+    vars
+        .iter()
+        .filter_map(|var| var.split_once('='))
+        .find(|(var_name, _)| *var_name == name)
+        .map(|(_, value)| value.to_string())
+        .or_else(|| std::env::var(name).ok())
+}
+
+fn parse_date_value(name: &str, value: &str) -> Result<TemplateValue, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(TemplateValue::Date(date));
+    }
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(TemplateValue::DateTime)
+        .ok_or_else(|| format!("variable {} is not a date or datetime: {}", name, value))
+}
+
+fn add_months(value: TemplateValue, months: i64) -> Option<TemplateValue> {
+    let date = match &value {
+        TemplateValue::Date(date) => *date,
+        TemplateValue::DateTime(datetime) => datetime.date(),
+        TemplateValue::Text(_) => return None,
+    };
+    let months_abs = chrono::Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+    let date = if months >= 0 {
+        date.checked_add_months(months_abs)?
+    } else {
+        date.checked_sub_months(months_abs)?
+    };
+    Some(match value {
+        TemplateValue::DateTime(datetime) => TemplateValue::DateTime(date.and_time(datetime.time())),
+        _ => TemplateValue::Date(date),
+    })
+}
+
+///amount of units, each unit_seconds long. None when it doesn't fit in chrono::Duration, which would panic
+fn checked_duration(amount: i64, unit_seconds: i64) -> Option<chrono::Duration> {
+    let seconds = amount.checked_mul(unit_seconds)?;
+    if seconds.checked_abs()? > i64::MAX / 1000 {
+        return None;
+    }
+    Some(chrono::Duration::seconds(seconds))
+}
+
+fn add_duration(value: TemplateValue, duration: chrono::Duration) -> Option<TemplateValue> {
+    match value {
+        TemplateValue::Date(date) if duration.num_seconds() % 86400 == 0 => date.checked_add_signed(duration).map(TemplateValue::Date),
+        TemplateValue::Date(date) => date.and_hms_opt(0, 0, 0).unwrap().checked_add_signed(duration).map(TemplateValue::DateTime),
+        TemplateValue::DateTime(datetime) => datetime.checked_add_signed(duration).map(TemplateValue::DateTime),
+        TemplateValue::Text(_) => None,
+    }
+}
+
+///evaluate expression: name followed by optional +/- amount with unit (s, min, h, d, w, mo, y) and | format.
+///Returns None if expression doesn't start with a name, so that it's not a template expression at all.
+fn evaluate(expression: &str, vars: &[String], now: NaiveDateTime) -> Option<Result<String, String>> {
+    let (expression, format) = match expression.split_once('|') {
+        Some((expression, format)) => (expression.trim(), Some(format.trim())),
+        None => (expression.trim(), None),
+    };
+    let name_end = expression.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(expression.len());
+    let name = &expression[..name_end];
+    if !is_identifier(name) {
+        return None;
+    }
+    let mut rest = expression[name_end..].trim_start();
+    Some((|| {
+        let mut value = match name {
+            "now" => TemplateValue::DateTime(now),
+            "today" => TemplateValue::Date(now.date()),
+            "month_start" => TemplateValue::Date(now.date().with_day(1).unwrap()),
+            "year_start" => TemplateValue::Date(NaiveDate::from_ymd_opt(now.year(), 1, 1).unwrap()),
+            _ => match lookup_var(name, vars) {
+                Some(text) if rest.is_empty() && format.is_none() => TemplateValue::Text(text),
+                Some(text) => parse_date_value(name, &text)?,
+                None => return Err(format!("undefined variable: {} (use --var {}=value)", name, name)),
+            },
+        };
+        while !rest.is_empty() {
+            let sign = match rest.chars().next() {
+                Some('+') => 1,
+                Some('-') => -1,
+                _ => return Err(format!("expected + or - in {}, got: {}", expression, rest)),
+            };
+            rest = rest[1..].trim_start();
+            let digits_end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let amount: i64 = match rest[..digits_end].parse::<i64>() {
+                Ok(amount) => sign * amount,
+                Err(_) => return Err(format!("expected number in {}, got: {}", expression, rest)),
+            };
+            rest = &rest[digits_end..];
+            let unit_end = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+            let result = match &rest[..unit_end] {
+                "s" => checked_duration(amount, 1).and_then(|duration| add_duration(value, duration)),
+                "min" => checked_duration(amount, 60).and_then(|duration| add_duration(value, duration)),
+                "h" => checked_duration(amount, 3600).and_then(|duration| add_duration(value, duration)),
+                "d" => checked_duration(amount, 86400).and_then(|duration| add_duration(value, duration)),
+                "w" => checked_duration(amount, 7 * 86400).and_then(|duration| add_duration(value, duration)),
+                "mo" => add_months(value, amount),
+                "y" => amount.checked_mul(12).and_then(|months| add_months(value, months)),
+                unit => return Err(format!("unknown unit {} in {} (expected s, min, h, d, w, mo or y)", unit, expression)),
+            };
+            value = match result {
+                Some(value) => value,
+                None => return Err(format!("date out of range in {}", expression)),
+            };
+            rest = rest[unit_end..].trim_start();
+        }
+        if let Some(format) = format {
+            if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                return Err(format!("invalid date format: {}", format));
+            }
+        }
+        Ok(match value {
+            TemplateValue::Text(text) => text,
+            TemplateValue::Date(date) => date.format(format.unwrap_or("%Y-%m-%d")).to_string(),
+            TemplateValue::DateTime(datetime) => datetime.format(format.unwrap_or("%Y-%m-%d %H:%M:%S")).to_string(),
+        })
+    })())
+}
+
+///replace {{ expression }} in query with its value. Braces that don't contain expression are left untouched
+pub fn render_template(template: &str, vars: &[String], now: NaiveDateTime) -> Result<String, String> {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+        result.push_str(&rest[..start]);
+        match evaluate(&rest[start + 2..end], vars, now) {
+            Some(value) => result.push_str(&value?),
+            None => result.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::NaiveDate;
+
+    use super::render_template;
+
+    #[test]
+    fn test_render_template() {
+        let now = NaiveDate::from_ymd_opt(2020, 3, 31).unwrap().and_hms_opt(10, 30, 0).unwrap();
+        let vars = vec!["table=events".to_string(), "day=2020-02-29".to_string(), "table=ignored".to_string()];
+        let render = |template| render_template(template, &vars, now);
+        assert_eq!(render("select * from {{table}}_{{ today - 1mo | %Y_%m }}"), Ok("select * from events_2020_02".to_string()));
+        assert_eq!(render("{{ today - 1d }} {{now + 1h - 30min}} {{ month_start }}"), Ok("2020-03-30 2020-03-31 11:00:00 2020-03-01".to_string()));
+        assert_eq!(render("{{ day + 1y }} {{ today - 12h }} {{ year_start | %Y }}"), Ok("2021-02-28 2020-03-30 12:00:00 2020".to_string()));
+        assert_eq!(render("select '{{1,2},{3,4}}'::int[]"), Ok("select '{{1,2},{3,4}}'::int[]".to_string()));
+        assert!(render("{{ missing }}").is_err());
+        assert!(render("{{ table + 1d }}").is_err());
+        assert!(render("{{ today - 1m }}").is_err());
+        assert!(render("{{ today | %Q }}").is_err());
+        assert!(render("{{ today + 9223372036854775807s }}").unwrap_err().contains("date out of range"));
+        assert!(render("{{ today - 9223372036854775807w }}").unwrap_err().contains("date out of range"));
+        assert!(render("{{ today + 4294967296mo }}").unwrap_err().contains("date out of range"));
+        assert!(render("{{ today + 922337203685477580y }}").unwrap_err().contains("date out of range"));
+    }
+
+}
@@ -0,0 +1,98 @@
+use std::fs;
+
+use native_tls::{Certificate, Identity, TlsConnector};
+
+
+pub const SSL_MODES: [&str; 5] = ["disable", "prefer", "require", "verify-ca", "verify-full"];
+
+///how connection is encrypted and how server certificate is checked, same as libpq sslmode
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SslMode {
+    ///plain connection
+    Disable,
+    ///encrypted if server supports it, certificate is not checked
+    Prefer,
+    ///encrypted, certificate is checked only when CA is given
+    Require,
+    ///encrypted, certificate must be signed by trusted CA
+    VerifyCa,
+    ///encrypted, certificate must be signed by trusted CA and match hostname
+    VerifyFull,
+}
+
+impl SslMode {
+
+    ///parse sslmode option, no value means plain connection
+    pub fn parse(sslmode: &Option<String>) -> Result<SslMode, String> {
+        match sslmode.as_deref() {
+            None | Some("disable") => Ok(SslMode::Disable),
+            Some("prefer") => Ok(SslMode::Prefer),
+            Some("require") => Ok(SslMode::Require),
+            Some("verify-ca") => Ok(SslMode::VerifyCa),
+            Some("verify-full") => Ok(SslMode::VerifyFull),
+            Some(other) => Err(format!("unknown sslmode: {} (expected one of: {})", other, SSL_MODES.join(", "))),
+        }
+    }
+
+    pub fn accept_invalid_certs(&self, has_ca: bool) -> bool {
+        match self {
+            SslMode::Disable | SslMode::Prefer => true,
+            SslMode::Require => !has_ca,
+            SslMode::VerifyCa | SslMode::VerifyFull => false,
+        }
+    }
+
+    pub fn accept_invalid_hostnames(&self) -> bool {
+        *self != SslMode::VerifyFull
+    }
+}
+
+fn read_file(kind: &str, path: &str) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| format!("can't read {} {}: {}", kind, path, e))
+}
+
+///read CA certificate in PEM or DER format
+pub fn read_certificate(path: &str) -> Result<Certificate, String> {
+    let data = read_file("CA certificate", path)?;
+    Certificate::from_pem(&data)
+        .or_else(|_| Certificate::from_der(&data))
+        .map_err(|e| format!("invalid CA certificate {}: {}", path, e))
+}
+
+///build TLS connector for given sslmode, CA certificate and client certificate and key (PEM)
+pub fn tls_connector(sslmode: SslMode, ssl_ca: &Option<String>, ssl_cert: &Option<String>, ssl_key: &Option<String>) -> Result<TlsConnector, String> {
+    let mut builder = TlsConnector::builder();
+    if let Some(ssl_ca) = ssl_ca {
+        builder.add_root_certificate(read_certificate(ssl_ca)?);
+    }
+    match (ssl_cert, ssl_key) {
+        (Some(ssl_cert), Some(ssl_key)) => {
+            let identity = Identity::from_pkcs8(&read_file("client certificate", ssl_cert)?, &read_file("client key", ssl_key)?)
+                .map_err(|e| format!("invalid client certificate or key (key must be PKCS#8 PEM): {}", e))?;
+            builder.identity(identity);
+        },
+        (None, None) => {},
+        _ => return Err("--ssl-cert and --ssl-key must be given together".to_string()),
+    }
+    builder.danger_accept_invalid_certs(sslmode.accept_invalid_certs(ssl_ca.is_some()));
+    builder.danger_accept_invalid_hostnames(sslmode.accept_invalid_hostnames());
+    builder.build().map_err(|e| format!("can't set up TLS: {}", e))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::SslMode;
+
+    #[test]
+    fn test_sslmode_verification() {
+        assert_eq!(SslMode::parse(&None), Ok(SslMode::Disable));
+        assert_eq!(SslMode::parse(&Some("verify-ca".to_string())), Ok(SslMode::VerifyCa));
+        assert!(SslMode::Require.accept_invalid_certs(false));
+        assert!(!SslMode::Require.accept_invalid_certs(true));
+        assert!(!SslMode::VerifyCa.accept_invalid_certs(false) && SslMode::VerifyCa.accept_invalid_hostnames());
+        assert!(!SslMode::VerifyFull.accept_invalid_certs(false) && !SslMode::VerifyFull.accept_invalid_hostnames());
+    }
+
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use crate::commands::export::TruncateOptions;
+use crate::utils::{truncate_text_with_note, TruncateUnit};
+
+///truncation limits for whole output and for selected columns
+#[derive(Clone, Debug, Default)]
+pub struct Truncation {
+    pub default: Option<u64>,
+    pub columns: HashMap<String, u64>,
+    pub unit: TruncateUnit,
+    //limit for each column index of current results
+    limits: Vec<Option<u64>>,
+}
+
+impl Truncation {
+
+    pub fn init(options: &TruncateOptions) -> Truncation {
+        Truncation {
+            default: options.truncate,
+            columns: options.truncate_column.iter().cloned().collect(),
+            unit: TruncateUnit::from_name(&options.truncate_unit),
+            limits: vec![],
+        }
+    }
+
+    ///resolve per column limits for new results
+    pub fn set_columns(&mut self, column_names: &[String]) {
+        let unknown: Vec<&String> = self.columns
+            .keys()
+            .filter(|name| !column_names.contains(name))
+            .collect();
+        if !unknown.is_empty() {
+            eprintln!("warning: --truncate-column given for unknown columns: {:?}", unknown);
+        }
+        self.limits = column_names
+            .iter()
+            .map(|name| self.columns.get(name).cloned().or(self.default))
+            .collect();
+    }
+
+    pub fn limit(&self, idx: usize) -> Option<u64> {
+        self.limits.get(idx).cloned().unwrap_or(self.default)
+    }
+
+    pub fn truncate_string(&self, idx: usize, value: &str) -> String {
+        truncate_text_with_note(value.to_string(), self.limit(idx), self.unit)
+    }
+
+    ///binary data is always truncated by bytes
+    pub fn truncate_bytes<'a>(&self, idx: usize, value: &'a [u8]) -> &'a [u8] {
+        match self.limit(idx) {
+            Some(max_length) if value.len() > max_length as usize => &value[..max_length as usize],
+            _ => value,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+
+    use super::Truncation;
+    use crate::utils::TruncateUnit;
+
+    #[test]
+    fn test_column_limits() {
+        let mut columns = HashMap::new();
+        columns.insert("body".to_string(), 2);
+        let mut truncation = Truncation {
+            default: Some(5),
+            columns,
+            unit: TruncateUnit::Chars,
+            limits: vec![],
+        };
+        truncation.set_columns(&["id".to_string(), "body".to_string()]);
+        assert_eq!(truncation.limit(0), Some(5));
+        assert_eq!(truncation.limit(1), Some(2));
+        assert_eq!(truncation.truncate_string(1, "abcd"), "ab ...(2 characters trimmed)");
+        assert_eq!(truncation.truncate_bytes(1, &[1, 2, 3]), &[1, 2]);
+    }
+
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use argon2::Argon2;
+use base64;
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, rand_core::RngCore};
+use rpassword;
+use serde_derive::{Deserialize, Serialize};
+use toml;
+
+use crate::config;
+#[cfg(unix)]
+use crate::utils::agent;
+
+
+///environment variable with vault passphrase, used instead of asking for it
+pub const PASSPHRASE_ENV: &str = "DBFISH_VAULT_PASSPHRASE";
+
+///name under which passphrase is remembered by password agent
+const AGENT_KEY: &str = "dbfish-vault";
+
+///vault file content, secrets are encrypted with key derived from passphrase
+#[derive(Deserialize, Serialize)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+///secrets stored in ~/.dbfish/vault, kept in memory with passphrase needed to save them again
+pub struct Vault {
+    passphrase: String,
+    pub secrets: BTreeMap<String, String>,
+}
+
+pub fn get_vault_path() -> PathBuf {
+    config::get_config_directory().join("vault")
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("can't derive vault key: {}", e))?;
+    Ok(Key::clone_from_slice(&key))
+}
+
+fn decode_field(name: &str, value: &str) -> Result<Vec<u8>, String> {
+    base64::decode(value).map_err(|_| format!("vault file is damaged: invalid {}", name))
+}
+
+pub fn encrypt(secrets: &BTreeMap<String, String>, passphrase: &str) -> Result<String, String> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let plaintext = toml::to_string(secrets).unwrap();
+    let data = cipher.encrypt(&nonce, plaintext.as_bytes()).map_err(|_| "can't encrypt vault".to_string())?;
+    let vault_file = VaultFile {
+        version: 1,
+        salt: base64::encode(salt),
+        nonce: base64::encode(nonce),
+        data: base64::encode(data),
+    };
+    Ok(toml::to_string(&vault_file).unwrap())
+}
+
+pub fn decrypt(content: &str, passphrase: &str) -> Result<BTreeMap<String, String>, String> {
+    let vault_file: VaultFile = toml::from_str(content).map_err(|e| format!("vault file is damaged: {}", e))?;
+    if vault_file.version != 1 {
+        return Err(format!("unsupported vault version: {}", vault_file.version));
+    }
+    let salt = decode_field("salt", &vault_file.salt)?;
+    let nonce = decode_field("nonce", &vault_file.nonce)?;
+    if nonce.len() != 12 {
+        return Err("vault file is damaged: invalid nonce".to_string());
+    }
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), decode_field("data", &vault_file.data)?.as_ref())
+        .map_err(|_| "can't open vault: wrong passphrase".to_string())?;
+    let plaintext = String::from_utf8(plaintext).map_err(|_| "vault file is damaged: invalid data".to_string())?;
+    toml::from_str(&plaintext).map_err(|e| format!("vault file is damaged: {}", e))
+}
+
+///passphrase from DBFISH_VAULT_PASSPHRASE, password agent, or asked for on terminal
+fn read_passphrase(confirm: bool) -> Result<String, String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    #[cfg(unix)]
+    if !confirm {
+        if let Some(passphrase) = agent::get(AGENT_KEY) {
+            return Ok(passphrase);
+        }
+    }
+    let prompt = |text| rpassword::prompt_password(text).map_err(|e| format!("can't read vault passphrase (it can be given with {}): {}", PASSPHRASE_ENV, e));
+    let passphrase = prompt("Vault passphrase: ")?;
+    if confirm && prompt("Repeat passphrase: ")? != passphrase {
+        return Err("passphrases don't match".to_string());
+    }
+    Ok(passphrase)
+}
+
+impl Vault {
+
+    ///open vault, asking for passphrase. If vault doesn't exist yet, new empty one is returned
+    ///if create is set, otherwise it's an error
+    pub fn open(create: bool) -> Result<Vault, String> {
+        let path = get_vault_path();
+        if !path.exists() {
+            if !create {
+                return Err(format!("vault {} does not exist, add secrets with: dbfish vault set NAME", path.display()));
+            }
+            eprintln!("Creating new vault: {}", path.display());
+            return Ok(Vault { passphrase: read_passphrase(true)?, secrets: BTreeMap::new() });
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| format!("can't read vault {}: {}", path.display(), e))?;
+        let passphrase = read_passphrase(false)?;
+        let secrets = decrypt(&content, &passphrase)?;
+        #[cfg(unix)]
+        agent::set(AGENT_KEY, &passphrase);
+        Ok(Vault { passphrase, secrets })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        config::ensure_config_directory_exists();
+        config::write_private_file(&get_vault_path(), encrypt(&self.secrets, &self.passphrase)?.as_bytes())
+            .map_err(|e| format!("can't write vault: {}", e))
+    }
+}
+
+///get single secret from vault
+pub fn get_secret(name: &str) -> Result<String, String> {
+    let vault = Vault::open(false)?;
+    match vault.secrets.get(name) {
+        Some(secret) => Ok(secret.clone()),
+        None => Err(format!("no secret named {} in vault, add it with: dbfish vault set {}", name, name)),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::BTreeMap;
+
+    use super::{decrypt, encrypt};
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let mut secrets = BTreeMap::new();
+        secrets.insert("prod".to_string(), "p@ss\"word".to_string());
+        let content = encrypt(&secrets, "correct horse").unwrap();
+        assert!(!content.contains("p@ss"));
+        assert_eq!(decrypt(&content, "correct horse"), Ok(secrets));
+        assert!(decrypt(&content, "wrong horse").is_err());
+    }
+
+}